@@ -1,2 +1,6 @@
 //! The execution engine behind a scriptkiddie AST
 //! Takes in an AST generated by a Parser and executes it sequentially
+
+/// Names in scope and property keys are resolved by their interned [`Symbol`], the same handle
+/// the lexer and parser produce, and the [`Interner`] they were handed on with
+pub use scriptkiddie_lexer::symbol::{Interner, Symbol};
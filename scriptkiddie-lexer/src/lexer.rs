@@ -1,87 +1,432 @@
 //! Lexical parsing logic
 
-use crate::token::Token;
+use std::borrow::Cow;
 
+use crate::{
+    span::{PositionEncoding, Span},
+    symbol::Interner,
+    token::{Token, TokenType, Trivia},
+};
+use goal::Goal;
+use regex::InputElement;
+use template::Brace;
+
+pub mod char_class;
+pub mod checkpoint;
+pub mod goal;
+pub mod incremental;
+#[cfg(feature = "jsx")]
+pub mod jsx;
 pub mod keyword;
 pub mod number;
 pub mod operator;
+pub mod recovery;
+pub mod regex;
 pub mod string;
+pub mod template;
 pub mod whitespace_comments;
 
+/// A result with error type LexError
+pub type Result<T> = std::result::Result<T, LexError>;
+
+/// An error returned when the input cannot be converted into a token, along with where it happened
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("{kind} at line {line}, column {column}")]
+pub struct LexError {
+    /// What went wrong
+    pub kind: LexErrorKind,
+    /// The line the offending token started on
+    pub line: usize,
+    /// The column the offending token started on
+    pub column: usize,
+}
+
+impl LexError {
+    /// Creates a new error of a certain kind at a line and column
+    pub fn new(kind: LexErrorKind, line: usize, column: usize) -> Self {
+        Self { kind, line, column }
+    }
+}
+
+/// All of the ways lexing can fail
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LexErrorKind {
+    /// A string literal was never closed
+    #[error("Unterminated string literal")]
+    UnterminatedString,
+    /// A template literal was never closed
+    #[error("Unterminated template literal")]
+    UnterminatedTemplate,
+    /// A regular expression literal was not closed before the end of its line
+    #[error("Unterminated regular expression literal")]
+    UnterminatedRegExp,
+    /// A regular expression literal with unknown or repeated flags
+    #[error("Invalid regular expression flags {0:?}")]
+    InvalidRegExpFlags(String),
+    /// A `/* */` comment was never closed
+    #[error("Unterminated block comment")]
+    UnterminatedBlockComment,
+    /// A character that cannot begin any token
+    #[error("Invalid character {0:?}")]
+    InvalidCharacter(char),
+    /// A numeric literal that could not be read as a number
+    #[error("Malformed number literal {0:?}")]
+    MalformedNumber(String),
+    /// An identifier or digit directly after a numeric literal, such as `3in`
+    #[error("Identifier starts immediately after numeric literal")]
+    IdentifierAfterNumber,
+    /// A reserved word written with escape sequences, such as `\u0069f`
+    #[error("Keywords cannot contain escape sequences")]
+    EscapedKeyword,
+    /// An escape sequence within a string that is not valid
+    #[error("Invalid escape sequence")]
+    InvalidEscape,
+    /// A line break within a string literal that is not escaped
+    #[error("Unescaped line terminator in string literal")]
+    LineTerminatorInString,
+    /// A legacy octal literal such as `017`, or an octal escape such as `\12`, in strict code
+    #[error("Legacy octal literals and escapes are not allowed in strict mode")]
+    LegacyOctal,
+}
+
+/// A place in the input, recorded where a token starts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Position {
+    /// The byte offset into the input
+    pub(crate) offset: usize,
+    /// The line, starting at 1
+    pub(crate) line: usize,
+    /// The column, starting at 1
+    pub(crate) column: usize,
+}
+
+/// Builds the text of a literal or identifier, borrowing it straight from the source until an
+/// escape sequence or other rewrite forces a copy
+#[derive(Clone, Debug)]
+pub(crate) struct TextBuilder<'src> {
+    /// The source text being borrowed from
+    source: &'src str,
+    /// Where the text starts in the source
+    start: usize,
+    /// How far into the source the text has been accounted for
+    end: usize,
+    /// The text once it no longer matches the source
+    owned: Option<String>,
+}
+
+impl<'src> TextBuilder<'src> {
+    /// Starts building text at a byte offset of the source
+    pub(crate) fn new(source: &'src str, start: usize) -> Self {
+        Self {
+            source,
+            start,
+            end: start,
+            owned: None,
+        }
+    }
+
+    /// Includes the source up to `end` in the text unchanged
+    pub(crate) fn include(&mut self, end: usize) {
+        if let Some(owned) = &mut self.owned {
+            owned.push_str(&self.source[self.end..end]);
+        }
+        self.end = end;
+    }
+
+    /// Replaces the source up to `end` with `replacement` in the text, such as when decoding an
+    /// escape sequence
+    pub(crate) fn replace(&mut self, replacement: Option<char>, end: usize) {
+        let owned = self
+            .owned
+            .get_or_insert_with(|| self.source[self.start..self.end].to_string());
+        owned.extend(replacement);
+        self.end = end;
+    }
+
+    /// The text built so far
+    pub(crate) fn as_str(&self) -> &str {
+        match &self.owned {
+            Some(owned) => owned,
+            None => &self.source[self.start..self.end],
+        }
+    }
+
+    /// Finishes the text, borrowing it if it was never changed from the source
+    pub(crate) fn finish(self) -> Cow<'src, str> {
+        match self.owned {
+            Some(owned) => Cow::Owned(owned),
+            None => Cow::Borrowed(&self.source[self.start..self.end]),
+        }
+    }
+}
+
 /// The lexer struct responsible for reading a stream of text and converting it into tokens. Can be
-/// treated as a token iterator
-pub struct Lexer {
+/// treated as a token iterator. Tokens borrow their text from the source wherever possible
+pub struct Lexer<'src> {
     /// The input context
-    input: Vec<char>,
-    /// The placement position of the input
+    source: &'src str,
+    /// The byte offset of the current position within the input
     pos: usize,
     /// The current line
     line: usize,
     /// The current column
     column: usize,
+    /// The unit columns are counted in
+    encoding: PositionEncoding,
+    /// Whether an error has been returned, after which no more tokens are produced
+    failed: bool,
+    /// Every `{` and `${` that is currently open, innermost last
+    braces: Vec<Brace>,
+    /// How a `/` beginning the next token should be read
+    input_element: InputElement,
+    /// Whether the last token was a `.` or `?.`, so the next is a property name
+    member_access: bool,
+    /// Whether a line terminator was skipped over before the token being collected
+    newline_before: bool,
+    /// Whitespace and comments seen so far, only recorded when enabled
+    trivia: Option<Vec<Trivia>>,
+    /// The goal the input is read with
+    goal: Goal,
+    /// Whether errors become invalid tokens rather than ending the input
+    recover: bool,
+    /// The interner names are interned into
+    interner: Interner,
+    /// Where the token being collected starts
+    token_start: Position,
+    /// Every JSX context that is currently open, innermost last
+    #[cfg(feature = "jsx")]
+    jsx: Vec<jsx::JsxFrame>,
+    /// Whether the last token closed an entire JSX element
+    #[cfg(feature = "jsx")]
+    jsx_closed: bool,
 }
 
-impl Lexer {
-    pub fn new(input: String) -> Self {
+impl<'src> Lexer<'src> {
+    /// Creates a lexer over some source text, skipping a leading byte order mark
+    pub fn new(source: &'src str) -> Self {
+        let pos = if source.starts_with(char_class::BOM) {
+            char_class::BOM.len_utf8()
+        } else {
+            0
+        };
+
         Self {
-            input: input.chars().collect(),
-            pos: 0,
+            source,
+            pos,
             line: 1,
             column: 1,
+            encoding: PositionEncoding::CodePoints,
+            failed: false,
+            braces: vec![],
+            input_element: InputElement::RegExp,
+            member_access: false,
+            newline_before: false,
+            trivia: None,
+            goal: Goal::Script,
+            recover: false,
+            interner: Interner::new(),
+            token_start: Position {
+                offset: pos,
+                line: 1,
+                column: 1,
+            },
+            #[cfg(feature = "jsx")]
+            jsx: vec![],
+            #[cfg(feature = "jsx")]
+            jsx_closed: false,
+        }
+    }
+
+    /// Sets the unit token and error columns are counted in. Lexers count code points unless told
+    /// otherwise
+    pub fn with_position_encoding(mut self, encoding: PositionEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Interns names into an existing interner rather than a new one, such as the one earlier
+    /// tokens of the same code were lexed with
+    pub fn with_interner(mut self, interner: Interner) -> Self {
+        self.interner = interner;
+        self
+    }
+
+    /// The interner the names of every token read so far resolve with
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    /// Stops lexing and takes the interner, to be handed on to the parser
+    pub fn into_interner(self) -> Interner {
+        self.interner
+    }
+
+    /// Reads the next token from the input, returning `None` once the input is exhausted or an
+    /// error has already been returned
+    pub fn next_token(&mut self) -> Option<Result<Token<'src>>> {
+        if self.failed {
+            return None;
         }
+
+        let token = match self.lex_token() {
+            Some(Err(err)) if self.recover => Some(Ok(self.recover_from(err))),
+            token => token,
+        };
+        match &token {
+            Some(Ok(token)) => {
+                self.input_element = InputElement::after(&token.token_type, self.member_access);
+                self.member_access = regex::is_member_access(&token.token_type);
+                // A whole JSX element ends an expression just like a literal does
+                #[cfg(feature = "jsx")]
+                if std::mem::take(&mut self.jsx_closed) {
+                    self.input_element = InputElement::Div;
+                }
+            }
+            Some(Err(_)) => self.failed = true,
+            None => {}
+        }
+
+        token
     }
 
-    pub fn next_token(&mut self) -> Option<Token> {
-        let mut token = None;
+    /// Skips any whitespace and comments and then collects the token that follows
+    fn lex_token(&mut self) -> Option<Result<Token<'src>>> {
+        let start_line = self.line;
+
         while let Some(c) = self.current_char() {
-            if c.is_whitespace() {
+            self.newline_before = self.line > start_line;
+            self.token_start = self.mark();
+            #[cfg(feature = "jsx")]
+            if let Some(token) = self.lex_jsx(c) {
+                return Some(token);
+            }
+            if char_class::is_whitespace(c) || char_class::is_line_terminator(c) {
                 self.skip_whitespace()
-            } else if c.is_alphabetic() || c == &'_' {
-                token = Some(self.collect_identifier_or_keyword());
-                break;
-            } else if c.is_ascii_digit() {
-                token = Some(self.collect_number());
-                break;
-            } else if c == &'"' || c == &'\'' {
-                token = Some(self.collect_string());
-                break;
-            } else if c == &'/' && self.peek_char() == Some(&'/') {
+            } else if char_class::is_identifier_start(c) || c == '\\' {
+                return Some(self.collect_identifier_or_keyword());
+            } else if c.is_ascii_digit()
+                || (c == '.' && self.peek_char().is_some_and(|c| c.is_ascii_digit()))
+            {
+                return Some(self.collect_number());
+            } else if c == '"' || c == '\'' {
+                return Some(self.collect_string());
+            } else if c == '`'
+                || (c == '}'
+                    && (self.in_substitution() || self.input_element == InputElement::TemplateTail))
+            {
+                return Some(self.collect_template());
+            } else if (c == '/' && self.peek_char() == Some('/')) || self.at_html_comment() {
                 self.skip_single_line_comment()
-            } else if c == &'/' && self.peek_char() == Some(&'*') {
-                self.skip_multi_line_comment()
+            } else if c == '#' && self.at_hashbang_comment() {
+                self.skip_hashbang_comment()
+            } else if c == '#' {
+                return Some(self.collect_private_name());
+            } else if c == '/' && self.peek_char() == Some('*') {
+                if let Err(err) = self.skip_multi_line_comment() {
+                    return Some(Err(err));
+                }
+            } else if c == '/' && self.input_element == InputElement::RegExp {
+                return Some(self.collect_regex());
             } else {
-                token = Some(self.collect_operator_or_punctuation());
-                break;
+                let token = self.collect_operator_or_punctuation();
+                if let Ok(token) = &token {
+                    self.track_braces(token);
+                }
+                return Some(token);
             }
         }
 
-        token
+        None
     }
 
-    /// Advances the lexer by 1 position, advancing line if need be
+    /// Advances the lexer by 1 character, advancing line if need be. A CRLF pair only counts as a
+    /// single line break
     fn advance(&mut self) {
-        if self.current_char() == Some(&'\n') {
-            self.line += 1;
-            self.column = 0;
-        } else {
-            self.column += 1;
+        let Some(c) = self.current_char() else {
+            return;
+        };
+
+        match c {
+            '\r' if self.peek_char() == Some('\n') => self.column += 1,
+            c if char_class::is_line_terminator(c) => {
+                self.line += 1;
+                self.column = 1;
+            }
+            c => self.column += self.encoding.width(c),
+        }
+        self.pos += c.len_utf8();
+    }
+
+    /// Records the current position, to be used as the start of a token
+    pub(crate) fn mark(&self) -> Position {
+        Position {
+            offset: self.pos,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    /// Creates a token spanning from `start` up to the current position
+    pub(crate) fn token_from(&self, start: Position, token_type: TokenType<'src>) -> Token<'src> {
+        let mut token = Token::new(
+            token_type,
+            Span::new(start.offset, self.pos),
+            start.line,
+            start.column,
+        );
+        token.newline_before = self.newline_before;
+
+        token
+    }
+
+    /// The source text from a byte offset up to the current position
+    pub(crate) fn source_from(&self, start: usize) -> &'src str {
+        &self.source[start..self.pos]
+    }
+
+    /// The source text from the current position to the end of the input
+    pub(crate) fn remaining(&self) -> &'src str {
+        &self.source[self.pos..]
+    }
+
+    /// Starts building text at the current position
+    pub(crate) fn text_builder(&self) -> TextBuilder<'src> {
+        TextBuilder::new(self.source, self.pos)
+    }
+
+    /// Decodes the character starting at a byte offset, taking a fast path for ASCII
+    fn char_at(&self, offset: usize) -> Option<char> {
+        match self.source.as_bytes().get(offset) {
+            Some(&byte) if byte.is_ascii() => Some(byte as char),
+            Some(_) => self.source[offset..].chars().next(),
+            None => None,
         }
-        self.pos += 1;
     }
 
     /// Gets the current character at the current position
-    fn current_char(&self) -> Option<&char> {
-        self.input.get(self.pos)
+    fn current_char(&self) -> Option<char> {
+        self.char_at(self.pos)
     }
 
     /// Peeks at the next char
-    fn peek_char(&self) -> Option<&char> {
-        self.input.get(self.pos + 1)
+    fn peek_char(&self) -> Option<char> {
+        self.peek_nth(1)
+    }
+
+    /// Peeks at the char `n` bytes ahead of the current position, where a peek of 0 is the
+    /// current char. This is meant for looking past ASCII, and gives `None` partway through a char
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        let offset = self.pos + n;
+        if !self.source.is_char_boundary(offset) {
+            return None;
+        }
+        self.char_at(offset)
     }
 }
 
-impl Iterator for Lexer {
-    type Item = Token;
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Result<Token<'src>>;
     fn next(&mut self) -> Option<Self::Item> {
         self.next_token()
     }
@@ -89,40 +434,47 @@ impl Iterator for Lexer {
 
 #[cfg(test)]
 mod tests {
-    use std::io::Read;
+    use std::{borrow::Cow, io::Read};
 
-    use crate::token::{Keyword, Operator, Punctuation, TokenType};
+    use crate::{
+        span::{LineIndex, PositionEncoding, Span},
+        token::{Keyword, Operator, Punctuation, TokenType},
+    };
 
-    use super::Lexer;
+    use super::{LexError, LexErrorKind, Lexer};
+    use crate::symbol::interner_with;
 
     #[test]
     fn lexer_iterator_full_lexing() {
+        let (interner, [a, b, c]) = interner_with(["a", "b", "c"]);
         let mut input_file = std::fs::File::open("../test/simple.js").expect("Failed to read file");
         let mut text = String::new();
         input_file
             .read_to_string(&mut text)
             .expect("Failed to read file");
 
-        let lexer = Lexer::new(text);
-        let tokens: Vec<_> = lexer.map(|token| token.token_type).collect();
+        let lexer = Lexer::new(&text).with_interner(interner);
+        let tokens: Vec<_> = lexer
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
 
         let should_be = vec![
             TokenType::Keyword(Keyword::Let),
-            TokenType::Identifier("a".to_string()),
+            TokenType::Identifier(a),
             TokenType::Operator(Operator::Assignment),
             TokenType::Number(100.0),
             TokenType::Punctuation(Punctuation::Semicolon),
             TokenType::Keyword(Keyword::Let),
-            TokenType::Identifier("b".to_string()),
+            TokenType::Identifier(b),
             TokenType::Operator(Operator::Assignment),
             TokenType::Number(20.0),
             TokenType::Punctuation(Punctuation::Semicolon),
             TokenType::Keyword(Keyword::Let),
-            TokenType::Identifier("c".to_string()),
+            TokenType::Identifier(c),
             TokenType::Operator(Operator::Assignment),
-            TokenType::Identifier("a".to_string()),
+            TokenType::Identifier(a),
             TokenType::Operator(Operator::Add),
-            TokenType::Identifier("b".to_string()),
+            TokenType::Identifier(b),
             TokenType::Punctuation(Punctuation::Semicolon),
         ];
 
@@ -131,16 +483,22 @@ mod tests {
 
     #[test]
     fn lexer_tokenizes_string_types() {
+        let (interner, [foo]) = interner_with(["foo"]);
         let input = "let foo = \"Hello!\";".to_string();
-        let lexer = Lexer::new(input);
+        let lexer = Lexer::new(&input).with_interner(interner);
 
-        let tokens: Vec<_> = lexer.map(|token| token.token_type).collect();
+        let tokens: Vec<_> = lexer
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
 
         let should_be = vec![
             TokenType::Keyword(Keyword::Let),
-            TokenType::Identifier("foo".to_string()),
+            TokenType::Identifier(foo),
             TokenType::Operator(Operator::Assignment),
-            TokenType::String("Hello!".to_string()),
+            TokenType::String {
+                cooked: "Hello!".into(),
+                raw: "\"Hello!\"".into(),
+            },
             TokenType::Punctuation(Punctuation::Semicolon),
         ];
 
@@ -149,24 +507,27 @@ mod tests {
 
     #[test]
     fn lexer_tokenizes_punctuation() {
+        let (interner, [i]) = interner_with(["i"]);
         let input = "for (let i = 0; i < 10; i++) {}".to_string();
-        let lexer = Lexer::new(input);
+        let lexer = Lexer::new(&input).with_interner(interner);
 
-        let tokens: Vec<_> = lexer.map(|token| token.token_type).collect();
+        let tokens: Vec<_> = lexer
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
 
         let should_be = vec![
             TokenType::Keyword(Keyword::For),
             TokenType::Punctuation(Punctuation::OpenParen),
             TokenType::Keyword(Keyword::Let),
-            TokenType::Identifier("i".to_string()),
+            TokenType::Identifier(i),
             TokenType::Operator(Operator::Assignment),
             TokenType::Number(0.0),
             TokenType::Punctuation(Punctuation::Semicolon),
-            TokenType::Identifier("i".to_string()),
+            TokenType::Identifier(i),
             TokenType::Operator(Operator::Lt),
             TokenType::Number(10.0),
             TokenType::Punctuation(Punctuation::Semicolon),
-            TokenType::Identifier("i".to_string()),
+            TokenType::Identifier(i),
             TokenType::Operator(Operator::Inc),
             TokenType::Punctuation(Punctuation::CloseParen),
             TokenType::Punctuation(Punctuation::OpenSquiggle),
@@ -178,6 +539,8 @@ mod tests {
 
     #[test]
     fn lexer_tokenizes_functions() {
+        let (interner, [foo, console, log, add, a, b, c]) =
+            interner_with(["foo", "Console", "log", "add", "a", "b", "c"]);
         let mut input_file =
             std::fs::File::open("../test/functions.js").expect("Failed to read file");
         let mut text = String::new();
@@ -185,59 +548,186 @@ mod tests {
             .read_to_string(&mut text)
             .expect("Failed to read file");
 
-        let lexer = Lexer::new(text);
-        let tokens: Vec<_> = lexer.map(|token| token.token_type).collect();
+        let lexer = Lexer::new(&text).with_interner(interner);
+        let tokens: Vec<_> = lexer
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
 
         let should_be = vec![
             TokenType::Keyword(Keyword::Function),
-            TokenType::Identifier("foo".to_string()),
+            TokenType::Identifier(foo),
             TokenType::Punctuation(Punctuation::OpenParen),
             TokenType::Punctuation(Punctuation::CloseParen),
             TokenType::Punctuation(Punctuation::OpenSquiggle),
-            TokenType::Identifier("Console".to_string()),
+            TokenType::Identifier(console),
             TokenType::Operator(Operator::Dot),
-            TokenType::Identifier("log".to_string()),
+            TokenType::Identifier(log),
             TokenType::Punctuation(Punctuation::OpenParen),
-            TokenType::String("Bar".to_string()),
+            TokenType::String {
+                cooked: "Bar".into(),
+                raw: "\"Bar\"".into(),
+            },
             TokenType::Punctuation(Punctuation::CloseParen),
             TokenType::Punctuation(Punctuation::Semicolon),
             TokenType::Punctuation(Punctuation::CloseSquiggle),
             TokenType::Keyword(Keyword::Function),
-            TokenType::Identifier("add".to_string()),
+            TokenType::Identifier(add),
             TokenType::Punctuation(Punctuation::OpenParen),
-            TokenType::Identifier("a".to_string()),
+            TokenType::Identifier(a),
             TokenType::Punctuation(Punctuation::Comma),
-            TokenType::Identifier("b".to_string()),
+            TokenType::Identifier(b),
             TokenType::Punctuation(Punctuation::CloseParen),
             TokenType::Punctuation(Punctuation::OpenSquiggle),
             TokenType::Keyword(Keyword::Return),
-            TokenType::Identifier("a".to_string()),
+            TokenType::Identifier(a),
             TokenType::Operator(Operator::Add),
-            TokenType::Identifier("b".to_string()),
+            TokenType::Identifier(b),
             TokenType::Punctuation(Punctuation::Semicolon),
             TokenType::Punctuation(Punctuation::CloseSquiggle),
             TokenType::Keyword(Keyword::Let),
-            TokenType::Identifier("a".to_string()),
+            TokenType::Identifier(a),
             TokenType::Operator(Operator::Assignment),
             TokenType::Number(100.0),
             TokenType::Punctuation(Punctuation::Semicolon),
             TokenType::Keyword(Keyword::Let),
-            TokenType::Identifier("b".to_string()),
+            TokenType::Identifier(b),
             TokenType::Operator(Operator::Assignment),
             TokenType::Number(5.6),
             TokenType::Punctuation(Punctuation::Semicolon),
             TokenType::Keyword(Keyword::Let),
-            TokenType::Identifier("c".to_string()),
+            TokenType::Identifier(c),
             TokenType::Operator(Operator::Assignment),
-            TokenType::Identifier("add".to_string()),
+            TokenType::Identifier(add),
             TokenType::Punctuation(Punctuation::OpenParen),
-            TokenType::Identifier("a".to_string()),
+            TokenType::Identifier(a),
             TokenType::Punctuation(Punctuation::Comma),
-            TokenType::Identifier("b".to_string()),
+            TokenType::Identifier(b),
             TokenType::Punctuation(Punctuation::CloseParen),
             TokenType::Punctuation(Punctuation::Semicolon),
         ];
 
         assert_eq!(tokens, should_be)
     }
+
+    #[test]
+    fn lexer_reports_invalid_characters() {
+        let input = "let a = @;".to_string();
+        let tokens: Vec<_> = Lexer::new(&input).collect();
+
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(
+            tokens[3],
+            Err(LexError::new(LexErrorKind::InvalidCharacter('@'), 1, 9))
+        );
+    }
+
+    #[test]
+    fn lexer_stops_after_an_error() {
+        let input = "/* never closed".to_string();
+        let mut lexer = Lexer::new(&input);
+
+        assert_eq!(
+            lexer.next(),
+            Some(Err(LexError::new(
+                LexErrorKind::UnterminatedBlockComment,
+                1,
+                1
+            )))
+        );
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn lexer_records_token_spans() {
+        let input = "let é = `a\nb`;\n  x".to_string();
+        let tokens: Vec<_> = Lexer::new(&input)
+            .map(|token| token.expect("Failed to lex token"))
+            .collect();
+
+        let spans: Vec<_> = tokens.iter().map(|token| token.span).collect();
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(0, 3),
+                Span::new(4, 6),
+                Span::new(7, 8),
+                Span::new(9, 14),
+                Span::new(14, 15),
+                Span::new(18, 19),
+            ]
+        );
+        assert_eq!(tokens[3].span.source_text(&input), "`a\nb`");
+
+        // Multi-line tokens are positioned where they start
+        assert_eq!((tokens[3].line, tokens[3].column), (1, 9));
+        assert_eq!((tokens[5].line, tokens[5].column), (3, 3));
+
+        let index = LineIndex::new(&input);
+        for token in tokens {
+            assert_eq!(
+                index.line_column(token.span.start),
+                (token.line, token.column)
+            );
+        }
+    }
+
+    #[test]
+    fn lexer_counts_columns_in_an_encoding() {
+        let input = "'😀漢' + x;\n  '\\u{1F600}' ? y".to_string();
+        let index = LineIndex::new(&input);
+
+        for (encoding, columns) in [
+            (PositionEncoding::Utf8, [1, 11, 13, 14, 3, 15, 17]),
+            (PositionEncoding::Utf16, [1, 7, 9, 10, 3, 15, 17]),
+            (PositionEncoding::CodePoints, [1, 6, 8, 9, 3, 15, 17]),
+        ] {
+            let tokens: Vec<_> = Lexer::new(&input)
+                .with_position_encoding(encoding)
+                .map(|token| token.expect("Failed to lex token"))
+                .collect();
+
+            let found: Vec<_> = tokens.iter().map(|token| token.column).collect();
+            assert_eq!(found, columns, "Columns in {encoding:?}");
+            for token in tokens {
+                assert_eq!(
+                    index.line_column_in(token.span.start, encoding),
+                    (token.line, token.column)
+                );
+            }
+        }
+
+        let err = Lexer::new("'é' @")
+            .with_position_encoding(PositionEncoding::Utf8)
+            .find_map(Result::err)
+            .expect("Lexing should fail");
+        assert_eq!(err.column, 6);
+    }
+
+    #[test]
+    fn lexer_flags_tokens_after_line_terminators() {
+        let input = "a\nb /* no break */ c /* a\nbreak */ d // comment\ne".to_string();
+        let flags: Vec<_> = Lexer::new(&input)
+            .map(|token| token.expect("Failed to lex token").newline_before)
+            .collect();
+
+        assert_eq!(flags, vec![false, true, false, true, true]);
+    }
+
+    #[test]
+    fn lexer_borrows_unescaped_text() {
+        let input = r"/re/g 'plain' 'esc\n' `tpl`";
+        let borrowed: Vec<_> = Lexer::new(input)
+            .map(
+                |token| match token.expect("Failed to lex token").token_type {
+                    TokenType::String { cooked: text, .. }
+                    | TokenType::RegExp { body: text, .. } => matches!(text, Cow::Borrowed(_)),
+                    TokenType::Template { raw, .. } => matches!(raw, Cow::Borrowed(_)),
+                    token_type => panic!("Unexpected token {token_type:?}"),
+                },
+            )
+            .collect();
+
+        // Only the string containing an escape needs its own copy
+        assert_eq!(borrowed, vec![true, true, false, true]);
+    }
 }
@@ -0,0 +1,103 @@
+//! Character classification for the lexer. ASCII characters are looked up in a table, anything
+//! else falls back to the Unicode properties the spec is defined in terms of
+
+/// The byte order mark, which is skipped at the start of input and otherwise treated as whitespace
+pub(crate) const BOM: char = '\u{FEFF}';
+/// Zero width non-joiner, which may appear within an identifier
+const ZWNJ: char = '\u{200C}';
+/// Zero width joiner, which may appear within an identifier
+const ZWJ: char = '\u{200D}';
+
+/// Class bit for characters that can begin an identifier
+const ID_START: u8 = 1 << 0;
+/// Class bit for characters that can continue an identifier
+const ID_PART: u8 = 1 << 1;
+/// Class bit for whitespace that does not end a line
+const WHITESPACE: u8 = 1 << 2;
+/// Class bit for characters that end a line
+const LINE_TERMINATOR: u8 = 1 << 3;
+
+/// The classes of every ASCII character
+static ASCII_CLASSES: [u8; 128] = {
+    let mut table = [0; 128];
+    let mut c = 0;
+    while c < 128 {
+        let byte = c as u8;
+        table[c] = match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'$' | b'_' => ID_START | ID_PART,
+            b'0'..=b'9' => ID_PART,
+            b'\t' | 0x0B | 0x0C | b' ' => WHITESPACE,
+            b'\n' | b'\r' => LINE_TERMINATOR,
+            _ => 0,
+        };
+        c += 1;
+    }
+    table
+};
+
+/// Whether an ASCII character belongs to a class
+fn ascii_is(c: char, class: u8) -> bool {
+    ASCII_CLASSES[c as usize] & class != 0
+}
+
+/// Whether a character can begin an identifier
+pub(crate) fn is_identifier_start(c: char) -> bool {
+    if c.is_ascii() {
+        ascii_is(c, ID_START)
+    } else {
+        unicode_ident::is_xid_start(c)
+    }
+}
+
+/// Whether a character can continue an identifier
+pub(crate) fn is_identifier_part(c: char) -> bool {
+    if c.is_ascii() {
+        ascii_is(c, ID_PART)
+    } else {
+        c == ZWNJ || c == ZWJ || unicode_ident::is_xid_continue(c)
+    }
+}
+
+/// Whether a character ends a line
+pub(crate) fn is_line_terminator(c: char) -> bool {
+    if c.is_ascii() {
+        ascii_is(c, LINE_TERMINATOR)
+    } else {
+        matches!(c, '\u{2028}' | '\u{2029}')
+    }
+}
+
+/// Whether a character is whitespace that does not end a line. This is tab, vertical tab, form
+/// feed, the byte order mark and anything in the Unicode space separator category
+pub(crate) fn is_whitespace(c: char) -> bool {
+    if c.is_ascii() {
+        ascii_is(c, WHITESPACE)
+    } else {
+        matches!(
+            c,
+            BOM | '\u{A0}' | '\u{1680}' | '\u{2000}'
+                ..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}'
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_identifier_part, is_identifier_start, is_line_terminator, is_whitespace};
+
+    #[test]
+    fn classifies_ascii_characters() {
+        assert!(is_identifier_start('$') && is_identifier_start('_') && is_identifier_start('z'));
+        assert!(!is_identifier_start('1') && is_identifier_part('1'));
+        assert!(is_whitespace('\t') && !is_whitespace('\n'));
+        assert!(is_line_terminator('\r') && !is_line_terminator(' '));
+    }
+
+    #[test]
+    fn classifies_unicode_characters() {
+        assert!(is_identifier_start('é') && is_identifier_start('π'));
+        assert!(!is_identifier_start('\u{200C}') && is_identifier_part('\u{200C}'));
+        assert!(is_whitespace('\u{3000}') && !is_whitespace('\u{85}'));
+        assert!(is_line_terminator('\u{2028}'));
+    }
+}
@@ -0,0 +1,167 @@
+//! Saving the lexer's place in the input and returning to it later, so a parser reading tokens as
+//! it goes can still backtrack, such as when a `(` turns out to begin an arrow function's
+//! parameters rather than a parenthesized expression
+
+use crate::token::Token;
+
+use super::{regex::InputElement, template::Brace, Lexer, Result};
+
+/// Everything needed to carry on lexing from a position in the input as though nothing after it
+/// had been read
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    /// The byte offset of the position
+    pos: usize,
+    /// The line of the position
+    line: usize,
+    /// The column of the position
+    column: usize,
+    /// Whether an error had been returned
+    failed: bool,
+    /// The braces open at the position
+    braces: Vec<Brace>,
+    /// How a `/` at the position would be read
+    input_element: InputElement,
+    /// Whether the token before the position was a `.` or `?.`
+    member_access: bool,
+    /// How much trivia had been recorded
+    trivia: usize,
+    /// The JSX contexts open at the position
+    #[cfg(feature = "jsx")]
+    jsx: Vec<super::jsx::JsxFrame>,
+}
+
+impl<'src> Lexer<'src> {
+    /// Saves the current place in the input, to be returned to with [`Lexer::rewind`]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            pos: self.pos,
+            line: self.line,
+            column: self.column,
+            failed: self.failed,
+            braces: self.braces.clone(),
+            input_element: self.input_element,
+            member_access: self.member_access,
+            trivia: self.trivia().len(),
+            #[cfg(feature = "jsx")]
+            jsx: self.jsx.clone(),
+        }
+    }
+
+    /// Returns to a place saved with [`Lexer::checkpoint`], forgetting every token and piece of
+    /// trivia read since. The checkpoint must have come from this lexer
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.pos = checkpoint.pos;
+        self.line = checkpoint.line;
+        self.column = checkpoint.column;
+        self.failed = checkpoint.failed;
+        self.braces = checkpoint.braces;
+        self.input_element = checkpoint.input_element;
+        self.member_access = checkpoint.member_access;
+        if let Some(trivia) = &mut self.trivia {
+            trivia.truncate(checkpoint.trivia);
+        }
+        #[cfg(feature = "jsx")]
+        {
+            self.jsx = checkpoint.jsx;
+            self.jsx_closed = false;
+        }
+    }
+
+    /// Returns to a checkpoint and reads the token there again with a different input element,
+    /// for when the parser finds the lexer guessed wrong about a `/` or a `}`
+    pub fn rescan(
+        &mut self,
+        checkpoint: Checkpoint,
+        input_element: InputElement,
+    ) -> Option<Result<Token<'src>>> {
+        self.rewind(checkpoint);
+        self.set_input_element(input_element);
+        self.next_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lexer::{regex::InputElement, Lexer},
+        symbol::interner_with,
+        token::{Operator, Punctuation, TemplatePart, TokenType},
+    };
+
+    /// Reads the type of the next token, panicking on any error
+    fn next(lexer: &mut Lexer<'_>) -> TokenType<'static> {
+        lexer
+            .next_token()
+            .expect("Expected a token")
+            .expect("Failed to lex token")
+            .token_type
+            .into_owned()
+    }
+
+    #[test]
+    fn rewinding_reads_the_same_tokens_again() {
+        let (interner, [a]) = interner_with(["a"]);
+        let mut lexer = Lexer::new("(a, `b${c}d`) => a\n/ 2")
+            .with_interner(interner)
+            .with_trivia();
+        next(&mut lexer);
+        let checkpoint = lexer.checkpoint();
+        let trivia = lexer.trivia().len();
+
+        let first: Vec<_> = (0..8).map(|_| next(&mut lexer)).collect();
+        assert!(lexer.trivia().len() > trivia);
+
+        lexer.rewind(checkpoint);
+        assert_eq!(lexer.trivia().len(), trivia);
+        let again: Vec<_> = (0..8).map(|_| next(&mut lexer)).collect();
+        assert_eq!(first, again);
+        assert_eq!(again[7], TokenType::Identifier(a));
+
+        // Positions carry on from where the checkpoint was taken
+        let token = lexer.next_token().unwrap().unwrap();
+        assert_eq!((token.line, token.column), (2, 1));
+    }
+
+    #[test]
+    fn rescanning_changes_how_a_slash_is_read() {
+        let mut lexer = Lexer::new("x) /a/g");
+        next(&mut lexer);
+        next(&mut lexer);
+
+        let checkpoint = lexer.checkpoint();
+        assert_eq!(next(&mut lexer), TokenType::Operator(Operator::Div));
+
+        let token = lexer.rescan(checkpoint, InputElement::RegExp);
+        assert_eq!(
+            token.unwrap().unwrap().token_type,
+            TokenType::RegExp {
+                body: "a".into(),
+                flags: "g".into(),
+            }
+        );
+        assert_eq!(lexer.next_token(), None);
+    }
+
+    #[test]
+    fn rescanning_continues_a_template() {
+        let mut lexer = Lexer::new("{ }x`");
+        next(&mut lexer);
+
+        let checkpoint = lexer.checkpoint();
+        assert_eq!(
+            next(&mut lexer),
+            TokenType::Punctuation(Punctuation::CloseSquiggle)
+        );
+
+        let token = lexer.rescan(checkpoint, InputElement::TemplateTail);
+        assert_eq!(
+            token.unwrap().unwrap().token_type,
+            TokenType::Template {
+                part: TemplatePart::Tail,
+                cooked: Some("x".into()),
+                raw: "x".into(),
+            }
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! The goal symbol the lexer reads its input as, which decides which words are reserved and which
+//! legacy syntax is allowed
+
+use super::Lexer;
+
+/// What kind of code the input is, named after the goal symbols of the spec
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Goal {
+    /// A classic script in sloppy mode, allowing legacy octal literals and HTML-like comments
+    #[default]
+    Script,
+    /// A script in strict mode, as if it began with `"use strict"`
+    StrictScript,
+    /// An ES module, which is always strict and also reserves `await`
+    Module,
+}
+
+impl Goal {
+    /// Whether code with this goal is strict mode code
+    pub fn is_strict(self) -> bool {
+        matches!(self, Goal::StrictScript | Goal::Module)
+    }
+
+    /// Whether the input is a module
+    pub fn is_module(self) -> bool {
+        self == Goal::Module
+    }
+}
+
+impl<'src> Lexer<'src> {
+    /// Sets the goal the input is read with. Lexers read a sloppy mode [`Goal::Script`] unless
+    /// told otherwise
+    pub fn with_goal(mut self, goal: Goal) -> Self {
+        self.goal = goal;
+        self
+    }
+
+    /// The goal the input is being read with
+    pub fn goal(&self) -> Goal {
+        self.goal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lexer::{LexErrorKind, Lexer},
+        symbol::interner_with,
+        token::{Keyword, Operator, TokenType},
+    };
+
+    use super::Goal;
+
+    /// Lexes some input with a goal, returning the first error if there is one
+    fn lex(input: &str, goal: Goal) -> Result<Vec<TokenType<'_>>, LexErrorKind> {
+        Lexer::new(input)
+            .with_goal(goal)
+            .map(|token| token.map(|token| token.token_type))
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.kind)
+    }
+
+    #[test]
+    fn strict_reserved_words_depend_on_goal() {
+        let sloppy = lex("implements package", Goal::Script).expect("Failed to lex");
+        // Interning the word gives the keyword's symbol all the same
+        assert_eq!(
+            sloppy[0],
+            TokenType::Identifier(Keyword::Implements.symbol())
+        );
+        assert_eq!(sloppy[1], TokenType::Identifier(Keyword::Package.symbol()));
+
+        let strict = lex("implements package", Goal::StrictScript).expect("Failed to lex");
+        assert_eq!(strict[0], TokenType::Keyword(Keyword::Implements));
+        assert_eq!(strict[1], TokenType::Keyword(Keyword::Package));
+
+        assert!(!Keyword::Yield.is_reserved(Goal::Script));
+        assert!(Keyword::Yield.is_reserved(Goal::StrictScript));
+    }
+
+    #[test]
+    fn await_is_reserved_in_modules() {
+        assert!(!Keyword::Await.is_reserved(Goal::StrictScript));
+        assert!(Keyword::Await.is_reserved(Goal::Module));
+
+        assert!(lex(r"aw\u0061it", Goal::Script).is_ok());
+        assert_eq!(
+            lex(r"aw\u0061it", Goal::Module),
+            Err(LexErrorKind::EscapedKeyword)
+        );
+    }
+
+    #[test]
+    fn legacy_octals_are_rejected_in_strict_code() {
+        for input in ["017", "09", r"'\12'", r"'\08'", r"'\9'"] {
+            assert!(lex(input, Goal::Script).is_ok());
+            assert_eq!(
+                lex(input, Goal::StrictScript),
+                Err(LexErrorKind::LegacyOctal)
+            );
+        }
+
+        assert!(lex(r"0 '\0'", Goal::Module).is_ok());
+    }
+
+    #[test]
+    fn html_comments_are_disabled_in_modules() {
+        let (interner, [a, b]) = interner_with(["a", "b"]);
+        let tokens: Vec<_> = Lexer::new("a <!-- b")
+            .with_goal(Goal::Module)
+            .with_interner(interner)
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Identifier(a),
+                TokenType::Operator(Operator::Lt),
+                TokenType::Operator(Operator::Not),
+                TokenType::Operator(Operator::Dec),
+                TokenType::Identifier(b),
+            ]
+        );
+        assert_eq!(lex("a <!-- b", Goal::Script).map(|t| t.len()), Ok(1));
+    }
+}
@@ -0,0 +1,353 @@
+//! Relexing only the part of the input affected by an edit, reusing the tokens around it
+
+use std::borrow::Cow;
+
+use crate::{
+    span::Span,
+    token::{Punctuation, TemplatePart, Token, TokenType, Trivia},
+};
+
+use super::{
+    regex::{is_member_access, InputElement},
+    template::Brace,
+    Lexer, Result,
+};
+
+/// A change to the source text, replacing a range of bytes with new text
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextEdit<'a> {
+    /// The byte range of the original text being replaced
+    pub span: Span,
+    /// The text it is replaced with
+    pub replacement: &'a str,
+}
+
+impl<'a> TextEdit<'a> {
+    /// Creates an edit replacing a range of bytes with new text
+    pub fn new(span: Span, replacement: &'a str) -> Self {
+        Self { span, replacement }
+    }
+
+    /// Applies the edit to the original text
+    pub fn apply(&self, source: &str) -> String {
+        let mut edited = String::with_capacity(source.len() + self.replacement.len());
+        edited.push_str(&source[..self.span.start]);
+        edited.push_str(self.replacement);
+        edited.push_str(&source[self.span.end..]);
+        edited
+    }
+
+    /// How many bytes text after the edit moves by
+    fn shift(&self) -> isize {
+        self.replacement.len() as isize - self.span.len() as isize
+    }
+}
+
+/// Updates the open braces for a token, mirroring what the lexer does while reading it
+fn replay_braces(braces: &mut Vec<Brace>, token_type: &TokenType<'_>) {
+    match token_type {
+        TokenType::Punctuation(Punctuation::OpenSquiggle) => braces.push(Brace::Block),
+        TokenType::Template {
+            part: TemplatePart::Head,
+            ..
+        } => braces.push(Brace::Substitution),
+        TokenType::Punctuation(Punctuation::CloseSquiggle)
+        | TokenType::Template {
+            part: TemplatePart::Tail,
+            ..
+        } => {
+            braces.pop();
+        }
+        _ => {}
+    }
+}
+
+/// The index of the first token opening a JSX element, which is a `<` where an expression may
+/// begin, or the number of tokens if there is none
+#[cfg(feature = "jsx")]
+fn first_jsx(tokens: &[Token<'_>]) -> usize {
+    let mut input_element = InputElement::RegExp;
+    let mut member_access = false;
+    for (index, token) in tokens.iter().enumerate() {
+        if input_element == InputElement::RegExp
+            && token.token_type == TokenType::Operator(crate::token::Operator::Lt)
+        {
+            return index;
+        }
+        input_element = InputElement::after(&token.token_type, member_access);
+        member_access = is_member_access(&token.token_type);
+    }
+
+    tokens.len()
+}
+
+impl<'src> Lexer<'src> {
+    /// Lexes edited input given the tokens of the text before the edit, reading only the tokens
+    /// around the edit again. Lexing stops as soon as it is back in step with the previous tokens,
+    /// which are then reused with their positions shifted. The lexer must be newly created over
+    /// the edited text, with the interner the previous tokens were lexed with. When it records
+    /// [trivia](Lexer::with_trivia), `previous_trivia` is the trivia of the text before the edit,
+    /// which is carried over in the same way so the lexer ends up with the trivia of all the text
+    pub fn relex(
+        &mut self,
+        previous: &[Token<'_>],
+        previous_trivia: &[Trivia],
+        edit: &TextEdit<'_>,
+    ) -> Result<Vec<Token<'src>>> {
+        let shift = edit.shift();
+        let edit_end = edit.span.start + edit.replacement.len();
+
+        // Open JSX elements are not known from tokens alone, so nothing from the first one on is
+        // replayed
+        #[cfg(feature = "jsx")]
+        let jsx_start = first_jsx(previous);
+        #[cfg(not(feature = "jsx"))]
+        let jsx_start = previous.len();
+
+        // Tokens ending before the edit are kept, except the last which may have looked ahead
+        // into it, such as the `?` before `.5`
+        let kept = previous
+            .iter()
+            .take_while(|token| token.span.end < edit.span.start)
+            .count()
+            .saturating_sub(1)
+            .min(jsx_start);
+
+        let mut tokens: Vec<_> = previous[..kept]
+            .iter()
+            .map(|token| self.reuse(token, 0))
+            .collect();
+        if let Some(last) = tokens.last() {
+            for token in &tokens {
+                replay_braces(&mut self.braces, &token.token_type);
+            }
+
+            self.pos = last.span.start;
+            self.line = last.line;
+            self.column = last.column;
+            while self.pos < last.span.end {
+                self.advance();
+            }
+            let property = tokens
+                .len()
+                .checked_sub(2)
+                .is_some_and(|before| is_member_access(&tokens[before].token_type));
+            self.input_element = InputElement::after(&last.token_type, property);
+            self.member_access = is_member_access(&last.token_type);
+
+            // This includes any byte order mark the lexer recorded when it was created
+            if let Some(trivia) = &mut self.trivia {
+                *trivia = previous_trivia
+                    .iter()
+                    .take_while(|piece| piece.span.end <= last.span.end)
+                    .cloned()
+                    .collect();
+            }
+        }
+
+        let mut old = kept;
+        let mut old_braces = self.braces.clone();
+        while let Some(token) = self.next_token() {
+            let token = token?;
+            if token.span.start < edit_end {
+                tokens.push(token);
+                continue;
+            }
+
+            // Catch up with the previous tokens, stopping at the one starting where this does
+            let old_start = token.span.start.saturating_add_signed(-shift);
+            while previous
+                .get(old)
+                .is_some_and(|old_token| old_token.span.start < old_start)
+            {
+                replay_braces(&mut old_braces, &previous[old].token_type);
+                old += 1;
+            }
+
+            let Some(old_token) = previous.get(old) else {
+                tokens.push(token);
+                continue;
+            };
+            replay_braces(&mut old_braces, &old_token.token_type);
+            let old_property = old
+                .checked_sub(1)
+                .is_some_and(|before| is_member_access(&previous[before].token_type));
+            old += 1;
+
+            // Neither the previous tokens nor the new ones may be within a JSX element here
+            #[cfg(feature = "jsx")]
+            let outside_jsx = self.jsx.is_empty();
+            #[cfg(not(feature = "jsx"))]
+            let outside_jsx = true;
+            let in_step = old_token.span.start == old_start
+                && old_token.span.len() == token.span.len()
+                && old_token.newline_before == token.newline_before
+                && old_token.token_type == token.token_type
+                && old_braces == self.braces
+                && self.input_element == InputElement::after(&token.token_type, old_property)
+                && old <= jsx_start
+                && outside_jsx;
+            if !in_step {
+                tokens.push(token);
+                continue;
+            }
+
+            // Everything after is unchanged, other than its position
+            let line_shift = token.line as isize - old_token.line as isize;
+            let column_shift = token.column as isize - old_token.column as isize;
+            tokens.push(token);
+            tokens.extend(previous[old..].iter().map(|reused| {
+                let mut token = self.reuse(reused, shift);
+                if reused.line == old_token.line {
+                    token.column = token.column.saturating_add_signed(column_shift);
+                }
+                token.line = token.line.saturating_add_signed(line_shift);
+                token
+            }));
+            if let Some(trivia) = &mut self.trivia {
+                let after = previous_trivia.partition_point(|piece| piece.span.end <= old_start);
+                trivia.extend(previous_trivia[after..].iter().map(|piece| {
+                    let span = Span::new(
+                        piece.span.start.saturating_add_signed(shift),
+                        piece.span.end.saturating_add_signed(shift),
+                    );
+                    Trivia::new(piece.kind.clone(), span)
+                }));
+            }
+            break;
+        }
+
+        Ok(tokens)
+    }
+
+    /// Moves a token from the text before an edit into the edited input, shifting its span. Any
+    /// text it borrowed is found again within its new span, where the source is unchanged
+    fn reuse(&self, token: &Token<'_>, shift: isize) -> Token<'src> {
+        let span = Span::new(
+            token.span.start.saturating_add_signed(shift),
+            token.span.end.saturating_add_signed(shift),
+        );
+        let within = span.source_text(self.source);
+        let token_type = token.token_type.clone().map_text(|text| match text {
+            Cow::Borrowed(text) => match within.find(text) {
+                Some(start) => Cow::Borrowed(&within[start..start + text.len()]),
+                None => Cow::Owned(text.to_string()),
+            },
+            Cow::Owned(text) => Cow::Owned(text),
+        });
+
+        Token {
+            token_type,
+            span,
+            line: token.line,
+            column: token.column,
+            newline_before: token.newline_before,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::Lexer, printer::print_lossless, span::Span};
+
+    use super::TextEdit;
+
+    /// Checks that relexing after an edit gives exactly the tokens and trivia of lexing the edited
+    /// text from scratch
+    fn assert_relexes(source: &str, start: usize, end: usize, replacement: &str) {
+        let mut lexer = Lexer::new(source).with_trivia();
+        let previous: Vec<_> = lexer
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("Failed to lex original");
+        let previous_trivia = lexer.take_trivia();
+        let interner = lexer.into_interner();
+        let edit = TextEdit::new(Span::new(start, end), replacement);
+        let edited = edit.apply(source);
+
+        let mut lexer = Lexer::new(&edited)
+            .with_interner(interner.clone())
+            .with_trivia();
+        let expected = lexer.by_ref().collect::<Result<Vec<_>, _>>();
+        let mut relexer = Lexer::new(&edited).with_interner(interner).with_trivia();
+        let relexed = relexer.relex(&previous, &previous_trivia, &edit);
+        assert_eq!(relexed, expected, "Relexing {edited:?}");
+        assert_eq!(relexer.trivia(), lexer.trivia(), "Trivia of {edited:?}");
+
+        if let Ok(tokens) = relexed {
+            let printed = print_lossless(&edited, &tokens, relexer.trivia());
+            assert_eq!(printed, edited);
+        }
+    }
+
+    #[test]
+    fn text_edits_apply_to_source() {
+        let edit = TextEdit::new(Span::new(4, 5), "bee");
+        assert_eq!(edit.apply("let a = 1;"), "let bee = 1;");
+    }
+
+    #[test]
+    fn relexing_matches_lexing_from_scratch() {
+        let source = "let a = 1;\nlet b = `x${ {c: a} }y${d}z` / 2;\nf(a?.b, 'q');\n  g(x)";
+
+        for (start, end, replacement) in [
+            (4, 5, "alpha"),
+            (8, 9, "1.5"),
+            (0, 0, "// note\n"),
+            (10, 11, "\n\n"),
+            (20, 21, "w"),
+            (26, 27, "{"),
+            (36, 36, "}`; `"),
+            (40, 41, "\\"),
+            (54, 56, "?."),
+            (60, 61, "'"),
+            (source.len(), source.len(), "\n/re/g"),
+            (0, source.len(), "x"),
+        ] {
+            assert_relexes(source, start, end, replacement);
+        }
+    }
+
+    #[cfg(feature = "jsx")]
+    #[test]
+    fn relexing_jsx_matches_lexing_from_scratch() {
+        let source = "x = <div a=\"1\">hi {y}</div>;\nz = a < b > c;";
+
+        for (start, end, replacement) in [
+            (9, 9, ""),
+            (4, 4, "1 "),
+            (4, 5, "<>a</>"),
+            (13, 16, "{2}"),
+            (26, 27, ""),
+            (31, 31, "<b/>"),
+            (34, 34, "<p>"),
+            (40, 41, "/"),
+        ] {
+            assert_relexes(source, start, end, replacement);
+        }
+    }
+
+    #[test]
+    fn relexing_shifts_lines_and_columns() {
+        let source = "a + b;\nc(d);\ne";
+        // Joining the first two lines moves every token after onto a different line
+        assert_relexes(source, 6, 7, " ");
+        assert_relexes(source, 1, 1, "\n\n  ");
+        assert_relexes(source, 4, 5, "longer");
+    }
+
+    #[test]
+    fn relexing_carries_trivia() {
+        let source = "\u{feff}a /* one */ + b; // two\r\nc(d) /* three */;\n";
+        let b = source.find('b').expect("Source has a b");
+
+        for (start, end, replacement) in [
+            (b, b + 1, "bee"),
+            (b, b, "/* new */ "),
+            (b + 1, b + 1, ";\n// more"),
+            (source.len() - 2, source.len() - 1, ""),
+        ] {
+            assert_relexes(source, start, end, replacement);
+        }
+    }
+}
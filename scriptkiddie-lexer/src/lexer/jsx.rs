@@ -0,0 +1,298 @@
+//! Lexer implementation for JSX elements, only built with the `jsx` feature. Tags, attributes and
+//! text children are read here, while the code within `{}` expression containers is read as usual
+
+use crate::token::{Operator, Punctuation, Token, TokenType};
+
+use super::{
+    char_class::{is_identifier_part, is_identifier_start, is_line_terminator, is_whitespace},
+    regex::InputElement,
+    LexError, LexErrorKind, Lexer, Result,
+};
+
+/// What the lexer is reading within a JSX element
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum JsxContext {
+    /// The inside of a tag, between its `<` and `>`
+    Tag(Tag),
+    /// The text and elements between an opening and closing tag
+    Children,
+}
+
+/// What has been read of a tag so far
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct Tag {
+    /// Whether anything other than a `/` has been read since the `<`
+    started: bool,
+    /// Whether the tag is a closing tag, `</a>`
+    closing: bool,
+    /// Whether the tag closes itself, `<a />`
+    self_closing: bool,
+}
+
+/// An open JSX context, along with how many braces were open when it began. A context is put on
+/// hold while more braces are open, which is exactly while inside a `{}` expression container
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct JsxFrame {
+    /// The JSX context
+    context: JsxContext,
+    /// The number of open braces the context applies at
+    depth: usize,
+}
+
+impl<'src> Lexer<'src> {
+    /// The JSX context at the current position, or `None` when reading regular code
+    fn jsx_context(&self) -> Option<JsxContext> {
+        self.jsx
+            .last()
+            .filter(|frame| frame.depth == self.braces.len())
+            .map(|frame| frame.context)
+    }
+
+    /// Starts a new JSX context at the current brace depth
+    fn push_jsx(&mut self, context: JsxContext) {
+        self.jsx.push(JsxFrame {
+            context,
+            depth: self.braces.len(),
+        });
+    }
+
+    /// Reads the next token if it is part of JSX, given the character it starts at. Returns
+    /// `None` if the token should be read as regular code, including the whitespace and comments
+    /// within tags
+    pub(crate) fn lex_jsx(&mut self, c: char) -> Option<Result<Token<'src>>> {
+        match self.jsx_context() {
+            Some(JsxContext::Children) => Some(self.collect_jsx_child(c)),
+            Some(JsxContext::Tag(tag)) => {
+                let at_comment = c == '/' && matches!(self.peek_char(), Some('/' | '*'));
+                if is_whitespace(c) || is_line_terminator(c) || at_comment {
+                    None
+                } else {
+                    Some(self.collect_jsx_tag_token(c, tag))
+                }
+            }
+            // A `<` where an expression may begin starts an element when a tag name or the `>`
+            // of a fragment follows
+            None if c == '<'
+                && self.input_element == InputElement::RegExp
+                && self
+                    .peek_char()
+                    .is_some_and(|c| is_identifier_start(c) || c == '>') =>
+            {
+                Some(Ok(self.collect_jsx_tag_open()))
+            }
+            None => None,
+        }
+    }
+
+    /// Reads the `<` starting a tag
+    fn collect_jsx_tag_open(&mut self) -> Token<'src> {
+        let start = self.mark();
+        self.advance();
+        self.push_jsx(JsxContext::Tag(Tag::default()));
+        self.token_from(start, TokenType::Operator(Operator::Lt))
+    }
+
+    /// Reads a child of an element: a nested tag, the `{` of an expression container or text
+    fn collect_jsx_child(&mut self, c: char) -> Result<Token<'src>> {
+        match c {
+            '<' => Ok(self.collect_jsx_tag_open()),
+            '{' => self.collect_operator_or_punctuation().inspect(|token| {
+                self.track_braces(token);
+            }),
+            _ => {
+                let start = self.mark();
+                while self.current_char().is_some_and(|c| c != '<' && c != '{') {
+                    self.advance();
+                }
+                let text = self.source_from(start.offset);
+                Ok(self.token_from(start, TokenType::JsxText(text.into())))
+            }
+        }
+    }
+
+    /// Reads a token within a tag: a name, an attribute's string value or punctuation
+    fn collect_jsx_tag_token(&mut self, c: char, mut tag: Tag) -> Result<Token<'src>> {
+        let start = self.mark();
+        if c != '/' {
+            tag.started = true;
+            self.set_jsx_tag(tag);
+        }
+
+        let token_type = match c {
+            c if is_identifier_start(c) => {
+                while self
+                    .current_char()
+                    .is_some_and(|c| is_identifier_part(c) || c == '-')
+                {
+                    self.advance();
+                }
+                {
+                    let name = self.source_from(start.offset);
+                    TokenType::JsxIdentifier(self.interner.intern(name))
+                }
+            }
+            '"' | '\'' => self.collect_jsx_string(c)?,
+            '{' => {
+                let token = self.collect_operator_or_punctuation()?;
+                self.track_braces(&token);
+                return Ok(token);
+            }
+            '/' => {
+                self.advance();
+                if tag.started {
+                    tag.self_closing = true;
+                } else {
+                    tag.closing = true;
+                }
+                self.set_jsx_tag(tag);
+                TokenType::Operator(Operator::Div)
+            }
+            '>' => {
+                self.advance();
+                self.jsx.pop();
+                if tag.closing {
+                    // Also leave the children of the element being closed
+                    self.jsx.pop();
+                } else if !tag.self_closing {
+                    self.push_jsx(JsxContext::Children);
+                }
+                self.jsx_closed = self.jsx_context().is_none();
+                TokenType::Operator(Operator::Gt)
+            }
+            // Punctuation within a tag never combines with what follows, as `=>` or `.5` would
+            '=' | '.' | ':' => {
+                self.advance();
+                match c {
+                    '=' => TokenType::Operator(Operator::Assignment),
+                    '.' => TokenType::Operator(Operator::Dot),
+                    _ => TokenType::Punctuation(Punctuation::Colon),
+                }
+            }
+            c => {
+                return Err(LexError::new(
+                    LexErrorKind::InvalidCharacter(c),
+                    start.line,
+                    start.column,
+                ))
+            }
+        };
+
+        Ok(self.token_from(start, token_type))
+    }
+
+    /// Updates the state of the innermost tag
+    fn set_jsx_tag(&mut self, tag: Tag) {
+        if let Some(frame) = self.jsx.last_mut() {
+            frame.context = JsxContext::Tag(tag);
+        }
+    }
+
+    /// Reads an attribute's string value. Unlike string literals these have no escape sequences
+    /// and may span multiple lines
+    fn collect_jsx_string(&mut self, quote: char) -> Result<TokenType<'src>> {
+        let start = self.mark();
+        self.advance();
+
+        while let Some(c) = self.current_char() {
+            self.advance();
+            if c == quote {
+                let raw = self.source_from(start.offset);
+                return Ok(TokenType::String {
+                    cooked: raw[1..raw.len() - 1].into(),
+                    raw: raw.into(),
+                });
+            }
+        }
+
+        Err(LexError::new(
+            LexErrorKind::UnterminatedString,
+            start.line,
+            start.column,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        symbol::{interner_with, lex_types},
+        token::{Operator, Punctuation, TokenType},
+    };
+
+    #[test]
+    fn lexer_reads_jsx_tags_and_text() {
+        let (interner, [my_el, a, b]) = interner_with(["my-el", "a", "b"]);
+        let tokens = lex_types(r#"<my-el a="x\y" b>Hi there</my-el>"#, interner);
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Operator(Operator::Lt),
+                TokenType::JsxIdentifier(my_el),
+                TokenType::JsxIdentifier(a),
+                TokenType::Operator(Operator::Assignment),
+                TokenType::String {
+                    cooked: r"x\y".into(),
+                    raw: r#""x\y""#.into(),
+                },
+                TokenType::JsxIdentifier(b),
+                TokenType::Operator(Operator::Gt),
+                TokenType::JsxText("Hi there".into()),
+                TokenType::Operator(Operator::Lt),
+                TokenType::Operator(Operator::Div),
+                TokenType::JsxIdentifier(my_el),
+                TokenType::Operator(Operator::Gt),
+            ]
+        );
+    }
+
+    #[test]
+    fn lexer_reads_jsx_expression_containers() {
+        let (interner, [c]) = interner_with(["c"]);
+        let tokens = lex_types("<A b={ {c: 1} }>{x > 1}<></></A> / 2", interner);
+
+        assert_eq!(tokens[4], TokenType::Punctuation(Punctuation::OpenSquiggle));
+        assert_eq!(tokens[6], TokenType::Identifier(c));
+        assert_eq!(
+            tokens[10],
+            TokenType::Punctuation(Punctuation::CloseSquiggle)
+        );
+        assert_eq!(tokens[11], TokenType::Operator(Operator::Gt));
+        // `>` within a container is an operator rather than the end of a tag
+        assert_eq!(tokens[14], TokenType::Operator(Operator::Gt));
+        assert_eq!(
+            tokens[16],
+            TokenType::Punctuation(Punctuation::CloseSquiggle)
+        );
+        // An empty fragment
+        assert_eq!(
+            tokens[17..22],
+            [
+                TokenType::Operator(Operator::Lt),
+                TokenType::Operator(Operator::Gt),
+                TokenType::Operator(Operator::Lt),
+                TokenType::Operator(Operator::Div),
+                TokenType::Operator(Operator::Gt),
+            ]
+        );
+        // The whole element is a complete expression, so the `/` after it is a division
+        assert_eq!(tokens[tokens.len() - 2], TokenType::Operator(Operator::Div));
+    }
+
+    #[test]
+    fn lexer_only_starts_jsx_where_an_expression_can() {
+        let (interner, [a, b, c]) = interner_with(["a", "b", "c"]);
+        let tokens = lex_types("a <b > c", interner);
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Identifier(a),
+                TokenType::Operator(Operator::Lt),
+                TokenType::Identifier(b),
+                TokenType::Operator(Operator::Gt),
+                TokenType::Identifier(c),
+            ]
+        );
+    }
+}
@@ -1,49 +1,117 @@
-//! Lexer implementations for reading keywords, identifiers and operators
+//! Lexer implementations for reading keywords, identifiers, private names and operators
 
 use crate::token::{Keyword, Token, TokenType};
 
-use super::Lexer;
+use super::{
+    char_class::{is_identifier_part, is_identifier_start},
+    LexError, LexErrorKind, Lexer, Result, TextBuilder,
+};
 
-impl Lexer {
-    /// Reads an entire identifier/keyword and registers it as such
-    pub(crate) fn collect_identifier_or_keyword(&mut self) -> Token {
-        let start = self.column;
-        let mut result = String::new();
+impl<'src> Lexer<'src> {
+    /// Reads an entire identifier/keyword and registers it as such. Identifiers may contain
+    /// `\u` escapes, though an escaped word is never treated as a keyword
+    pub(crate) fn collect_identifier_or_keyword(&mut self) -> Result<Token<'src>> {
+        let start = self.mark();
+        let (name, escaped) = self.collect_identifier_name()?;
+        let goal = self.goal;
+
+        let token_type = match Keyword::to_keyword(name.as_str()) {
+            // Words only reserved in strict code are plain identifiers elsewhere
+            Some(keyword) if !escaped && (keyword.is_contextual() || keyword.is_reserved(goal)) => {
+                TokenType::Keyword(keyword)
+            }
+            Some(keyword) if escaped && keyword.is_reserved(goal) => {
+                return Err(LexError::new(
+                    LexErrorKind::EscapedKeyword,
+                    start.line,
+                    start.column,
+                ))
+            }
+            _ => TokenType::Identifier(self.interner.intern(name.as_str())),
+        };
+
+        Ok(self.token_from(start, token_type))
+    }
+
+    /// Reads a private name such as `#count`, which may be any identifier name including a
+    /// reserved word
+    pub(crate) fn collect_private_name(&mut self) -> Result<Token<'src>> {
+        let start = self.mark();
+        self.advance();
+        if !self
+            .current_char()
+            .is_some_and(|c| is_identifier_start(c) || c == '\\')
+        {
+            return Err(LexError::new(
+                LexErrorKind::InvalidCharacter('#'),
+                start.line,
+                start.column,
+            ));
+        }
+
+        let (name, _) = self.collect_identifier_name()?;
+        let token_type = TokenType::PrivateName(self.interner.intern(name.as_str()));
+        Ok(self.token_from(start, token_type))
+    }
+
+    /// Reads the characters of an identifier name, decoding any `\u` escapes. Also returns
+    /// whether any escapes were decoded
+    fn collect_identifier_name(&mut self) -> Result<(TextBuilder<'src>, bool)> {
+        let start = self.pos;
+        let mut name = self.text_builder();
+        let mut escaped = false;
 
         while let Some(c) = self.current_char() {
-            if c.is_alphanumeric() || c == &'_' || c == &'$' {
-                result.push(*c);
+            let valid = if self.pos == start {
+                is_identifier_start
+            } else {
+                is_identifier_part
+            };
+
+            if c == '\\' {
+                let escape_column = self.column;
+                let invalid = LexError::new(LexErrorKind::InvalidEscape, self.line, escape_column);
                 self.advance();
+                if self.current_char() != Some('u') {
+                    return Err(invalid);
+                }
+                self.advance();
+
+                match self.collect_unicode_escape_value() {
+                    Ok(c) if valid(c) => name.replace(Some(c), self.pos),
+                    _ => return Err(invalid),
+                }
+                escaped = true;
+            } else if valid(c) {
+                self.advance();
+                name.include(self.pos);
             } else {
                 break;
             }
         }
 
-        let token_type = if let Some(keyword) = Keyword::to_keyword(&result) {
-            TokenType::Keyword(keyword)
-        } else {
-            TokenType::Identifier(result)
-        };
-
-        Token::new(token_type, self.line, start)
+        Ok((name, escaped))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        lexer::Lexer,
+        lexer::{goal::Goal, LexErrorKind, Lexer},
+        symbol::interner_with,
         token::{Keyword, TokenType},
     };
 
     #[test]
     fn lexer_reads_an_identifier() {
         let input = "foo = 100".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let token = lexer.collect_identifier_or_keyword();
+        let token = lexer
+            .collect_identifier_or_keyword()
+            .expect("Failed to lex identifier");
         if let TokenType::Identifier(word) = token.token_type {
-            assert_eq!("foo", word)
+            assert_eq!(lexer.interner().resolve(word), "foo")
         } else {
             panic!("Token was not an identifier")
         }
@@ -52,13 +120,142 @@ mod tests {
     #[test]
     fn lexer_reads_a_keyword() {
         let input = "let foo = 100".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let token = lexer.collect_identifier_or_keyword();
+        let token = lexer
+            .collect_identifier_or_keyword()
+            .expect("Failed to lex identifier");
         if let TokenType::Keyword(word) = token.token_type {
             assert_eq!(Keyword::Let, word)
         } else {
             panic!("Token was not a keyword")
         }
     }
+
+    #[test]
+    fn lexer_reads_switch_keywords() {
+        let input = "case break".to_string();
+        let tokens: Vec<_> = Lexer::new(&input)
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Keyword(Keyword::Case),
+                TokenType::Keyword(Keyword::Break)
+            ]
+        )
+    }
+
+    #[test]
+    fn contextual_keywords_can_be_identifiers() {
+        let input = "async of typeof".to_string();
+        let tokens: Vec<_> = Lexer::new(&input)
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
+
+        assert_eq!(tokens[0], TokenType::Keyword(Keyword::Async));
+        assert_eq!(
+            tokens[0].as_identifier(Goal::Script),
+            Some(Keyword::Async.symbol())
+        );
+        assert_eq!(
+            tokens[1].as_identifier(Goal::Module),
+            Some(Keyword::Of.symbol())
+        );
+        assert_eq!(tokens[2].as_identifier(Goal::Script), None);
+        assert_eq!(tokens[2].identifier_name(), Some(Keyword::Typeof.symbol()));
+    }
+
+    #[test]
+    fn reserved_keywords_depend_on_the_goal() {
+        let token = |keyword| TokenType::Keyword(keyword);
+
+        let await_ = token(Keyword::Await);
+        assert_eq!(
+            await_.as_identifier(Goal::StrictScript),
+            Some(Keyword::Await.symbol())
+        );
+        assert_eq!(await_.as_identifier(Goal::Module), None);
+
+        for keyword in [Keyword::Yield, Keyword::Let, Keyword::Static] {
+            assert_eq!(
+                token(keyword.clone()).as_identifier(Goal::Script),
+                Some(keyword.symbol())
+            );
+            assert_eq!(
+                token(keyword.clone()).as_identifier(Goal::StrictScript),
+                None
+            );
+            assert_eq!(token(keyword).as_identifier(Goal::Module), None);
+        }
+    }
+
+    #[test]
+    fn lexer_reads_unicode_identifiers() {
+        let input = r"$foo _bar baz$ café πa a\u{62}c".to_string();
+        let mut lexer = Lexer::new(&input);
+        let tokens: Vec<_> = lexer
+            .by_ref()
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
+
+        let names: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| token.as_identifier(Goal::Script))
+            .map(|name| lexer.interner().resolve(name))
+            .collect();
+        assert_eq!(names, vec!["$foo", "_bar", "baz$", "café", "πa", "abc"]);
+    }
+
+    #[test]
+    fn lexer_reads_joiners_within_identifiers() {
+        let input = "a\u{200C}b".to_string();
+        let mut lexer = Lexer::new(&input);
+
+        let token = lexer
+            .collect_identifier_or_keyword()
+            .expect("Failed to lex identifier");
+        let name = token
+            .token_type
+            .as_identifier(Goal::Script)
+            .expect("Token was not an identifier");
+        assert_eq!(lexer.interner().resolve(name), "a\u{200C}b")
+    }
+
+    #[test]
+    fn lexer_rejects_escaped_keywords() {
+        let mut lexer = Lexer::new(r"\u0069f");
+        let err = lexer
+            .collect_identifier_or_keyword()
+            .expect_err("Escaped keyword should be rejected");
+        assert_eq!(err.kind, LexErrorKind::EscapedKeyword);
+
+        let mut lexer = Lexer::new(r"\u0030abc");
+        let err = lexer
+            .collect_identifier_or_keyword()
+            .expect_err("Identifier cannot start with a digit");
+        assert_eq!(err.kind, LexErrorKind::InvalidEscape);
+    }
+
+    #[test]
+    fn lexer_reads_private_names() {
+        let input = r"this.#count #if in obj #\u{61}b";
+        let (interner, [count, if_, ab]) = interner_with(["count", "if", "ab"]);
+        let tokens: Vec<_> = Lexer::new(input)
+            .with_interner(interner)
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
+
+        assert_eq!(tokens[2], TokenType::PrivateName(count));
+        assert_eq!(tokens[3], TokenType::PrivateName(if_));
+        assert_eq!(tokens[6], TokenType::PrivateName(ab));
+
+        let mut lexer = Lexer::new("# a");
+        let err = lexer
+            .collect_private_name()
+            .expect_err("A private name needs an identifier");
+        assert_eq!(err.kind, LexErrorKind::InvalidCharacter('#'));
+    }
 }
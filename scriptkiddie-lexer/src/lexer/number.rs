@@ -2,44 +2,224 @@
 
 use crate::token::{Token, TokenType};
 
-use super::Lexer;
+use super::{
+    char_class::is_identifier_start, LexError, LexErrorKind, Lexer, Position, Result, TextBuilder,
+};
 
-impl Lexer {
-    /// Collects an entire number as a token
-    pub(crate) fn collect_number(&mut self) -> Token {
-        let start = self.column;
-        let mut number_str = String::new();
-        let mut has_decimal = false;
+/// Computes the value of a string of digits in a given radix, rounded correctly however large it
+/// is by going through its exact decimal digits
+fn radix_value(digits: &str, radix: u32) -> f64 {
+    radix_to_decimal(digits, radix)
+        .parse()
+        .expect("Decimal digits always parse")
+}
+
+/// Converts a string of digits in a given radix into its decimal digits without losing precision
+fn radix_to_decimal(digits: &str, radix: u32) -> String {
+    /// Each limb holds nine decimal digits
+    const LIMB: u64 = 1_000_000_000;
+
+    // Little endian limbs of the value in base 10^9
+    let mut limbs: Vec<u64> = vec![0];
+    for digit in digits.chars().filter_map(|c| c.to_digit(radix)) {
+        let mut carry = digit as u64;
+        for limb in limbs.iter_mut() {
+            let value = *limb * radix as u64 + carry;
+            *limb = value % LIMB;
+            carry = value / LIMB;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+
+    let mut decimal = limbs.pop().unwrap_or_default().to_string();
+    for limb in limbs.iter().rev() {
+        decimal.push_str(&format!("{limb:09}"));
+    }
+
+    decimal
+}
+
+impl<'src> Lexer<'src> {
+    /// Collects an entire number as a token. Handles decimal literals with fractions and
+    /// exponents, `0x`/`0o`/`0b` prefixed integers, `_` separators, legacy octal literals and
+    /// `n` suffixed BigInt literals
+    pub(crate) fn collect_number(&mut self) -> Result<Token<'src>> {
+        let start = self.mark();
+
+        let radix = match (self.current_char(), self.peek_char()) {
+            (Some('0'), Some('x' | 'X')) => Some(16),
+            (Some('0'), Some('o' | 'O')) => Some(8),
+            (Some('0'), Some('b' | 'B')) => Some(2),
+            _ => None,
+        };
+
+        let token_type = if let Some(radix) = radix {
+            self.advance();
+            self.advance();
+
+            let mut digits = self.text_builder();
+            if !self.collect_digits(radix, &mut digits, start)? {
+                return Err(self.malformed_number(start));
+            }
+
+            if self.current_char() == Some('n') {
+                self.advance();
+                TokenType::BigInt(radix_to_decimal(digits.as_str(), radix).into())
+            } else {
+                TokenType::Number(radix_value(digits.as_str(), radix))
+            }
+        } else if self.current_char() == Some('0')
+            && self.peek_char().is_some_and(|c| c.is_ascii_digit())
+        {
+            if self.goal.is_strict() {
+                return Err(LexError::new(
+                    LexErrorKind::LegacyOctal,
+                    start.line,
+                    start.column,
+                ));
+            }
+            TokenType::Number(self.collect_legacy_octal_like(start)?)
+        } else {
+            let mut digits = self.text_builder();
+            self.collect_digits(10, &mut digits, start)?;
+
+            // A leading zero can only be followed by a separator in `0_1`, which is not allowed
+            if digits.as_str().len() > 1 && digits.as_str().starts_with('0') {
+                return Err(self.malformed_number(start));
+            }
+
+            if self.current_char() == Some('n') && !digits.as_str().is_empty() {
+                self.advance();
+                TokenType::BigInt(digits.finish())
+            } else {
+                TokenType::Number(self.collect_fraction_and_exponent(digits, start)?)
+            }
+        };
+
+        if let Some(c) = self.current_char() {
+            if is_identifier_start(c) || c == '\\' || c.is_ascii_digit() {
+                return Err(LexError::new(
+                    LexErrorKind::IdentifierAfterNumber,
+                    self.line,
+                    self.column,
+                ));
+            }
+        }
+
+        Ok(self.token_from(start, token_type))
+    }
+
+    /// Collects the optional `.` fraction and `e` exponent that follow the integer part of a
+    /// decimal literal, and then parses the whole literal
+    fn collect_fraction_and_exponent(
+        &mut self,
+        mut number_str: TextBuilder<'src>,
+        start: Position,
+    ) -> Result<f64> {
+        if self.current_char() == Some('.') {
+            self.advance();
+            number_str.include(self.pos);
+            self.collect_digits(10, &mut number_str, start)?;
+        }
+
+        if let Some('e' | 'E') = self.current_char() {
+            self.advance();
+            if let Some('+' | '-') = self.current_char() {
+                self.advance();
+            }
+            number_str.include(self.pos);
+            if !self.collect_digits(10, &mut number_str, start)? {
+                return Err(self.malformed_number(start));
+            }
+        }
+
+        number_str
+            .as_str()
+            .parse::<f64>()
+            .map_err(|_| self.malformed_number(start))
+    }
+
+    /// Collects a number starting with `0` followed by more digits. This is a legacy octal literal
+    /// (`017`) if every digit is octal, and otherwise a decimal literal with a leading zero (`089`)
+    fn collect_legacy_octal_like(&mut self, start: Position) -> Result<f64> {
+        let mut digits = self.text_builder();
+        while self.current_char().is_some_and(|c| c.is_ascii_digit()) {
+            self.advance();
+        }
+        digits.include(self.pos);
+
+        if digits.as_str().chars().all(|c| c.is_digit(8)) {
+            return Ok(radix_value(digits.as_str(), 8));
+        }
+
+        if self.current_char() == Some('_') {
+            return Err(self.malformed_number(start));
+        }
+
+        self.collect_fraction_and_exponent(digits, start)
+    }
+
+    /// Collects a run of digits in a given radix into `digits`, skipping over `_` separators. A
+    /// separator must sit between two digits. Returns whether any digits were read
+    fn collect_digits(
+        &mut self,
+        radix: u32,
+        digits: &mut TextBuilder<'src>,
+        start: Position,
+    ) -> Result<bool> {
+        let mut read_any = false;
+        let mut after_separator = false;
 
         while let Some(c) = self.current_char() {
-            if c.is_ascii_digit() {
-                number_str.push(*c);
+            if c.is_digit(radix) {
                 self.advance();
-            } else if c == &'.' && !has_decimal {
-                has_decimal = true;
-                number_str.push(*c);
+                digits.include(self.pos);
+                read_any = true;
+                after_separator = false;
+            } else if c == '_' {
+                if !read_any || after_separator {
+                    return Err(self.malformed_number(start));
+                }
                 self.advance();
+                digits.replace(None, self.pos);
+                after_separator = true;
             } else {
                 break;
             }
         }
 
-        let number_value = number_str.parse::<f64>().unwrap_or(0.0);
+        if after_separator {
+            return Err(self.malformed_number(start));
+        }
+
+        Ok(read_any)
+    }
 
-        Token::new(TokenType::Number(number_value), self.line, start)
+    /// Creates a malformed number error showing the literal as written up to the current position
+    fn malformed_number(&self, start: Position) -> LexError {
+        LexError::new(
+            LexErrorKind::MalformedNumber(self.source_from(start.offset).to_string()),
+            start.line,
+            start.column,
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{lexer::Lexer, token::TokenType};
+    use crate::{
+        lexer::{LexErrorKind, Lexer},
+        token::TokenType,
+    };
 
     #[test]
     fn lexer_collect_number() {
         let input = "150".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let number_token = lexer.collect_number();
+        let number_token = lexer.collect_number().expect("Failed to lex number");
         if let TokenType::Number(num) = number_token.token_type {
             assert_eq!(150.0, num)
         } else {
@@ -48,15 +228,124 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn lexer_collect_number_with_decimal() {
         let input = "3.14".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let number_token = lexer.collect_number();
+        let number_token = lexer.collect_number().expect("Failed to lex number");
         if let TokenType::Number(num) = number_token.token_type {
             assert_eq!(3.14, num)
         } else {
             panic!("Token was not a number")
         }
     }
+
+    fn lex_number(input: &str) -> f64 {
+        let mut lexer = Lexer::new(input);
+        match lexer
+            .collect_number()
+            .expect("Failed to lex number")
+            .token_type
+        {
+            TokenType::Number(num) => num,
+            _ => panic!("Token was not a number"),
+        }
+    }
+
+    #[test]
+    fn lexer_collect_prefixed_integers() {
+        assert_eq!(255.0, lex_number("0xFF"));
+        assert_eq!(15.0, lex_number("0o17"));
+        assert_eq!(10.0, lex_number("0b1010"));
+
+        // Just above 2^53, where rounding each digit in turn gives a different value
+        let correct = 2f64.powi(57) + 32.0;
+        assert_eq!(correct, lex_number("0x200000000000011"));
+        assert_eq!(correct, lex_number("0o10000000000000000021"));
+        assert_eq!(correct, lex_number("010000000000000000021"));
+    }
+
+    #[test]
+    fn lexer_reports_malformed_numbers_as_written() {
+        for (input, written) in [("0x", "0x"), ("0b2", "0b"), ("0x_1", "0x"), ("1__0", "1_")] {
+            let err = Lexer::new(input)
+                .collect_number()
+                .expect_err("Number should be rejected");
+            assert_eq!(err.kind, LexErrorKind::MalformedNumber(written.into()));
+        }
+    }
+
+    #[test]
+    fn lexer_collect_exponents_and_fractions() {
+        assert_eq!(1e-7, lex_number("1e-7"));
+        assert_eq!(250.0, lex_number("2.5E2"));
+        assert_eq!(0.5, lex_number(".5"));
+        assert_eq!(1.0, lex_number("1."));
+    }
+
+    #[test]
+    fn lexer_collect_numeric_separators() {
+        assert_eq!(1_000_000.0, lex_number("1_000_000"));
+        assert_eq!(0xFFFF as f64, lex_number("0xFF_FF"));
+
+        for input in ["1__0", "1_", "0x_1", "1._5", "1e_5", "0_1"] {
+            let mut lexer = Lexer::new(input);
+            let err = lexer
+                .collect_number()
+                .expect_err("Separator should be rejected");
+            assert!(matches!(err.kind, LexErrorKind::MalformedNumber(_)));
+        }
+    }
+
+    #[test]
+    fn lexer_collect_legacy_octal() {
+        assert_eq!(15.0, lex_number("017"));
+        assert_eq!(89.0, lex_number("089"));
+        assert_eq!(8.5, lex_number("08.5"));
+    }
+
+    #[test]
+    fn lexer_rejects_identifier_after_number() {
+        for input in ["3in", "0x1g", "1e5x"] {
+            let mut lexer = Lexer::new(input);
+            let err = lexer
+                .collect_number()
+                .expect_err("Identifier should not follow a number");
+            assert_eq!(err.kind, LexErrorKind::IdentifierAfterNumber);
+        }
+    }
+
+    fn lex_bigint(input: &str) -> String {
+        let mut lexer = Lexer::new(input);
+        match lexer
+            .collect_number()
+            .expect("Failed to lex number")
+            .token_type
+        {
+            TokenType::BigInt(digits) => digits.into_owned(),
+            _ => panic!("Token was not a BigInt"),
+        }
+    }
+
+    #[test]
+    fn lexer_collect_bigint() {
+        assert_eq!("123", lex_bigint("123n"));
+        assert_eq!("0", lex_bigint("0n"));
+        assert_eq!("1000000", lex_bigint("1_000_000n"));
+        assert_eq!("255", lex_bigint("0xFFn"));
+        assert_eq!("18446744073709551615", lex_bigint("0xFFFF_FFFF_FFFF_FFFFn"));
+        assert_eq!(
+            "9007199254740993123456789",
+            lex_bigint("9007199254740993123456789n")
+        );
+    }
+
+    #[test]
+    fn lexer_rejects_non_integer_bigint() {
+        for input in ["1.5n", "1e3n", "017n"] {
+            let mut lexer = Lexer::new(input);
+            assert!(lexer.collect_number().is_err());
+        }
+    }
 }
@@ -2,56 +2,72 @@
 
 use crate::token::{Operator, Punctuation, Token, TokenType};
 
-use super::Lexer;
+use super::{LexError, LexErrorKind, Lexer, Result};
+
+/// The length of the longest punctuator, `>>>=`
+const MAX_PUNCTUATOR_LEN: usize = 4;
+
+impl<'src> Lexer<'src> {
+    /// Collects an operator or punctuation token, always preferring the longest punctuator that
+    /// matches the upcoming characters. Punctuators are all ASCII, so only the ASCII bytes ahead
+    /// are tried
+    pub(crate) fn collect_operator_or_punctuation(&mut self) -> Result<Token<'src>> {
+        let start = self.mark();
+        let remaining = self.remaining();
+        let ascii_len = remaining
+            .bytes()
+            .take(MAX_PUNCTUATOR_LEN)
+            .take_while(u8::is_ascii)
+            .count();
+
+        for len in (1..=ascii_len).rev() {
+            let text = &remaining[..len];
+
+            // `?.5` is a conditional followed by a number rather than an optional chain
+            if text == "?." && self.peek_nth(2).is_some_and(|c| c.is_ascii_digit()) {
+                continue;
+            }
 
-impl Lexer {
-    /// Collects an operator or punctuation token
-    pub(crate) fn collect_operator_or_punctuation(&mut self) -> Token {
-        let start = self.column;
-        let mut c = self.current_char().unwrap().to_string();
+            let token_type = if let Some(op) = Operator::to_operator(text) {
+                TokenType::Operator(op)
+            } else if let Some(punc) = Punctuation::to_puncutation(text) {
+                TokenType::Punctuation(punc)
+            } else {
+                continue;
+            };
 
-        match self.peek_char() {
-            Some(&'=') => {
-                c += "=";
-                self.advance();
-            }
-            Some(&'+') => {
-                c += "+";
+            for _ in 0..len {
                 self.advance();
             }
-            Some(&'-') => {
-                c += "-";
-                self.advance();
-            }
-            _ => {}
-        }
 
-        let token_type = if let Some(op) = Operator::to_operator(&c) {
-            TokenType::Operator(op)
-        } else {
-            let punc = Punctuation::to_puncutation(&c).unwrap();
-            TokenType::Punctuation(punc)
-        };
-
-        self.advance();
+            return Ok(self.token_from(start, token_type));
+        }
 
-        Token::new(token_type, self.line, start)
+        let invalid = self.current_char().unwrap_or_default();
+        Err(LexError::new(
+            LexErrorKind::InvalidCharacter(invalid),
+            start.line,
+            start.column,
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        lexer::Lexer,
+        lexer::{LexErrorKind, Lexer},
+        symbol::interner_with,
         token::{Operator, Punctuation, TokenType},
     };
 
     #[test]
     fn lexer_collects_operators() {
         let input = "*2".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let operator = lexer.collect_operator_or_punctuation();
+        let operator = lexer
+            .collect_operator_or_punctuation()
+            .expect("Failed to lex operator");
         if let TokenType::Operator(op) = operator.token_type {
             assert_eq!(op, Operator::Mult)
         } else {
@@ -62,9 +78,11 @@ mod tests {
     #[test]
     fn lexer_collects_eq_operators() {
         let input = ">=2".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let operator = lexer.collect_operator_or_punctuation();
+        let operator = lexer
+            .collect_operator_or_punctuation()
+            .expect("Failed to lex operator");
         if let TokenType::Operator(op) = operator.token_type {
             assert_eq!(op, Operator::Gte)
         } else {
@@ -75,9 +93,11 @@ mod tests {
     #[test]
     fn lexer_collects_punctuation() {
         let input = ";".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let operator = lexer.collect_operator_or_punctuation();
+        let operator = lexer
+            .collect_operator_or_punctuation()
+            .expect("Failed to lex operator");
         if let TokenType::Punctuation(punc) = operator.token_type {
             assert_eq!(Punctuation::Semicolon, punc)
         } else {
@@ -88,13 +108,83 @@ mod tests {
     #[test]
     fn lexer_collects_inc_dec() {
         let input = "++".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let operator = lexer.collect_operator_or_punctuation();
+        let operator = lexer
+            .collect_operator_or_punctuation()
+            .expect("Failed to lex operator");
         if let TokenType::Operator(op) = operator.token_type {
             assert_eq!(op, Operator::Inc)
         } else {
             panic!("Token was not an operator")
         }
     }
+
+    #[test]
+    fn lexer_rejects_unknown_characters() {
+        let input = "@".to_string();
+        let mut lexer = Lexer::new(&input);
+
+        let err = lexer
+            .collect_operator_or_punctuation()
+            .expect_err("@ is not an operator");
+        assert_eq!(err.kind, LexErrorKind::InvalidCharacter('@'));
+    }
+
+    #[test]
+    fn lexer_stops_operators_at_non_ascii() {
+        let mut lexer = Lexer::new("+\u{2603}");
+
+        let operator = lexer
+            .collect_operator_or_punctuation()
+            .expect("Failed to lex operator");
+        assert_eq!(operator.token_type, TokenType::Operator(Operator::Add));
+        let err = lexer
+            .collect_operator_or_punctuation()
+            .expect_err("A snowman is not an operator");
+        assert_eq!(err.kind, LexErrorKind::InvalidCharacter('\u{2603}'));
+    }
+
+    #[test]
+    fn lexer_collects_longest_punctuator() {
+        let input = ">>>=1".to_string();
+        let mut lexer = Lexer::new(&input);
+
+        let operator = lexer
+            .collect_operator_or_punctuation()
+            .expect("Failed to lex operator");
+        assert_eq!(
+            operator.token_type,
+            TokenType::Operator(Operator::UShrAssign)
+        );
+    }
+
+    #[test]
+    fn lexer_splits_mismatched_punctuators() {
+        let (interner, [a, b, c, x]) = interner_with(["a", "b", "c", "x"]);
+        let input = "a?.b ?? c(-1)?.5:x".to_string();
+        let lexer = Lexer::new(&input).with_interner(interner);
+
+        let tokens: Vec<_> = lexer
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
+
+        let should_be = vec![
+            TokenType::Identifier(a),
+            TokenType::Operator(Operator::OptionalChain),
+            TokenType::Identifier(b),
+            TokenType::Operator(Operator::Nullish),
+            TokenType::Identifier(c),
+            TokenType::Punctuation(Punctuation::OpenParen),
+            TokenType::Operator(Operator::Sub),
+            TokenType::Number(1.0),
+            TokenType::Punctuation(Punctuation::CloseParen),
+            TokenType::Punctuation(Punctuation::Question),
+            TokenType::Number(0.5),
+            TokenType::Punctuation(Punctuation::Colon),
+            TokenType::Identifier(x),
+        ];
+
+        assert_eq!(tokens, should_be)
+    }
 }
@@ -0,0 +1,99 @@
+//! Error recovery for the lexer, so that editors can keep reading past bad input
+
+use crate::token::{Token, TokenType};
+
+use super::{char_class::is_identifier_part, LexError, Lexer};
+
+impl<'src> Lexer<'src> {
+    /// Keeps lexing after an error. Bad input becomes a [`TokenType::Invalid`] token holding the
+    /// reason, instead of an error that ends the input
+    pub fn with_recovery(mut self) -> Self {
+        self.recover = true;
+        self
+    }
+
+    /// Turns an error into an invalid token spanning the bad input, skipping far enough past it
+    /// that lexing can carry on
+    pub(crate) fn recover_from(&mut self, err: LexError) -> Token<'src> {
+        let start = self.token_start;
+        if self.pos == start.offset {
+            self.advance();
+        }
+
+        // The rest of a malformed word is skipped too, such as the `in` of `3in`
+        while self.current_char().is_some_and(is_identifier_part) {
+            self.advance();
+        }
+
+        self.token_from(start, TokenType::Invalid(err.kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lexer::{LexErrorKind, Lexer},
+        span::Span,
+        symbol::{interner_with, Interner},
+        token::{Keyword, Operator, TokenType},
+    };
+
+    /// Lexes some input with recovery and an interner, returning each token's type and span
+    fn lex(input: &str, interner: Interner) -> Vec<(TokenType<'_>, Span)> {
+        Lexer::new(input)
+            .with_recovery()
+            .with_interner(interner)
+            .map(|token| token.expect("Recovering lexers never fail"))
+            .map(|token| (token.token_type, token.span))
+            .collect()
+    }
+
+    #[test]
+    fn lexer_recovers_from_invalid_characters() {
+        let (interner, [a, b]) = interner_with(["a", "b"]);
+        let tokens = lex("a @ 1__0 b", interner);
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenType::Identifier(a), Span::new(0, 1)),
+                (
+                    TokenType::Invalid(LexErrorKind::InvalidCharacter('@')),
+                    Span::new(2, 3)
+                ),
+                (
+                    TokenType::Invalid(LexErrorKind::MalformedNumber("1_".into())),
+                    Span::new(4, 8)
+                ),
+                (TokenType::Identifier(b), Span::new(9, 10)),
+            ]
+        );
+    }
+
+    #[test]
+    fn lexer_recovers_from_unterminated_strings() {
+        let tokens = lex("let s = 'abc\nlet t = '\\x' + 1;", Interner::new());
+        let types: Vec<_> = tokens
+            .into_iter()
+            .map(|(token_type, _)| token_type)
+            .collect();
+
+        assert_eq!(
+            types[3],
+            TokenType::Invalid(LexErrorKind::LineTerminatorInString)
+        );
+        assert_eq!(types[4], TokenType::Keyword(Keyword::Let));
+        // A bad escape only spoils its own string
+        assert_eq!(types[7], TokenType::Invalid(LexErrorKind::InvalidEscape));
+        assert_eq!(types[8], TokenType::Operator(Operator::Add));
+        assert_eq!(types.len(), 11);
+    }
+
+    #[test]
+    fn lexer_without_recovery_stops_at_errors() {
+        let mut lexer = Lexer::new("a @ b");
+
+        assert!(lexer.nth(1).is_some_and(|token| token.is_err()));
+        assert!(lexer.next().is_none());
+    }
+}
@@ -0,0 +1,259 @@
+//! Lexer implementation for regular expression literals, along with deciding whether a `/` starts
+//! one or is a division
+
+use crate::token::{Keyword, Operator, Punctuation, TemplatePart, Token, TokenType};
+
+use super::{
+    char_class::{is_identifier_part, is_line_terminator},
+    LexError, LexErrorKind, Lexer, Result,
+};
+
+/// The flags a regular expression literal may have
+const REGEXP_FLAGS: &str = "dgimsuvy";
+
+/// Which kind of token a `/` should begin, named after the lexical goal symbols of the spec
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputElement {
+    /// A `/` is the division operator (or `/=`)
+    Div,
+    /// A `/` begins a regular expression literal
+    RegExp,
+    /// A `}` continues a template literal as though it ended a `${` substitution, and a `/` is
+    /// the division operator. Only ever chosen by a parser, since the lexer already reads the `}`
+    /// ending a substitution it has seen begin as part of the template
+    TemplateTail,
+}
+
+impl InputElement {
+    /// The input element to use for the token following `token_type`, where `property` is whether
+    /// that token came straight after a `.` or `?.`. A `/` directly after something that ends an
+    /// expression is a division, anywhere else it begins a regular expression
+    pub fn after(token_type: &TokenType, property: bool) -> Self {
+        let ends_expression = match token_type {
+            TokenType::Identifier(_)
+            | TokenType::PrivateName(_)
+            | TokenType::Number(_)
+            | TokenType::BigInt(_)
+            | TokenType::String { .. }
+            | TokenType::RegExp { .. } => true,
+            #[cfg(feature = "jsx")]
+            TokenType::JsxText(_) | TokenType::JsxIdentifier(_) => true,
+            TokenType::Template { part, .. } => {
+                matches!(part, TemplatePart::NoSubstitution | TemplatePart::Tail)
+            }
+            TokenType::Invalid(_) => false,
+            // Any keyword can be a property name, as in `obj.get / 2`
+            TokenType::Keyword(_) if property => true,
+            TokenType::Keyword(keyword) => match keyword {
+                Keyword::This | Keyword::Super | Keyword::Null | Keyword::True | Keyword::False => {
+                    true
+                }
+                // These take an operand, which may be a regular expression
+                Keyword::Yield | Keyword::Await => false,
+                // Anything else that may be a name, such as `async` or `of`
+                keyword => keyword.is_contextual() || keyword.is_strict_reserved(),
+            },
+            TokenType::Operator(op) => matches!(op, Operator::Inc | Operator::Dec),
+            TokenType::Punctuation(punc) => matches!(
+                punc,
+                Punctuation::CloseParen | Punctuation::CloseBracket | Punctuation::CloseSquiggle
+            ),
+        };
+
+        if ends_expression {
+            InputElement::Div
+        } else {
+            InputElement::RegExp
+        }
+    }
+}
+
+/// Whether a token is a `.` or `?.`, after which even a keyword is a property name
+pub(crate) fn is_member_access(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::Operator(Operator::Dot | Operator::OptionalChain)
+    )
+}
+
+impl<'src> Lexer<'src> {
+    /// Overrides how a `/` beginning the next token is read. The lexer otherwise decides from the
+    /// previous token, which a parser may know better in cases such as a `/` after the `)` of an
+    /// `if` condition
+    pub fn set_input_element(&mut self, input_element: InputElement) {
+        self.input_element = input_element;
+    }
+
+    /// Collects a regular expression literal such as `/ab+c/gi`
+    pub(crate) fn collect_regex(&mut self) -> Result<Token<'src>> {
+        let start = self.mark();
+        let unterminated =
+            LexError::new(LexErrorKind::UnterminatedRegExp, start.line, start.column);
+        self.advance();
+
+        let body_start = self.pos;
+        let mut in_class = false;
+
+        let body = loop {
+            let c = match self.current_char() {
+                Some(c) if !is_line_terminator(c) => c,
+                _ => return Err(unterminated),
+            };
+            let end = self.pos;
+            self.advance();
+
+            match c {
+                '/' if !in_class => break &self.source[body_start..end],
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '\\' => match self.current_char() {
+                    Some(escaped) if !is_line_terminator(escaped) => self.advance(),
+                    _ => return Err(unterminated),
+                },
+                _ => {}
+            }
+        };
+
+        let flags_start = self.mark();
+        while self.current_char().is_some_and(is_identifier_part) {
+            self.advance();
+        }
+        let flags = self.source_from(flags_start.offset);
+
+        let invalid_flag = flags
+            .char_indices()
+            .any(|(i, flag)| !REGEXP_FLAGS.contains(flag) || flags[..i].contains(flag));
+        if invalid_flag {
+            return Err(LexError::new(
+                LexErrorKind::InvalidRegExpFlags(flags.to_string()),
+                start.line,
+                flags_start.column,
+            ));
+        }
+
+        Ok(self.token_from(
+            start,
+            TokenType::RegExp {
+                body: body.into(),
+                flags: flags.into(),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lexer::{LexErrorKind, Lexer},
+        symbol::{interner_with, lex_types, Interner},
+        token::{Operator, Punctuation, TokenType},
+    };
+
+    use super::InputElement;
+
+    fn regex<'a>(body: &'a str, flags: &'a str) -> TokenType<'a> {
+        TokenType::RegExp {
+            body: body.into(),
+            flags: flags.into(),
+        }
+    }
+
+    #[test]
+    fn lexer_collects_regex() {
+        let input = "/ab+c/gi".to_string();
+        let mut lexer = Lexer::new(&input);
+
+        let token = lexer.collect_regex().expect("Failed to lex regex");
+        assert_eq!(token.token_type, regex("ab+c", "gi"))
+    }
+
+    #[test]
+    fn lexer_collects_regex_with_slash_in_class() {
+        let (interner, [x]) = interner_with(["x"]);
+        let tokens = lex_types(r"x = /[/\]]+\//;", interner);
+
+        let should_be = vec![
+            TokenType::Identifier(x),
+            TokenType::Operator(Operator::Assignment),
+            regex(r"[/\]]+\/", ""),
+            TokenType::Punctuation(Punctuation::Semicolon),
+        ];
+
+        assert_eq!(tokens, should_be)
+    }
+
+    #[test]
+    fn lexer_tells_division_from_regex() {
+        let (interner, [a, b, c, x]) = interner_with(["a", "b", "c", "x"]);
+        let tokens = lex_types("a / b / c(1) /2/ [x]/ /y/g", interner);
+
+        let should_be = vec![
+            TokenType::Identifier(a),
+            TokenType::Operator(Operator::Div),
+            TokenType::Identifier(b),
+            TokenType::Operator(Operator::Div),
+            TokenType::Identifier(c),
+            TokenType::Punctuation(Punctuation::OpenParen),
+            TokenType::Number(1.0),
+            TokenType::Punctuation(Punctuation::CloseParen),
+            TokenType::Operator(Operator::Div),
+            TokenType::Number(2.0),
+            TokenType::Operator(Operator::Div),
+            TokenType::Punctuation(Punctuation::OpenBracket),
+            TokenType::Identifier(x),
+            TokenType::Punctuation(Punctuation::CloseBracket),
+            TokenType::Operator(Operator::Div),
+            regex("y", "g"),
+        ];
+
+        assert_eq!(tokens, should_be)
+    }
+
+    #[test]
+    fn lexer_divides_after_keywords_used_as_names() {
+        for input in [
+            "let total = obj.get / 2",
+            "a.of / b / c",
+            "x = async / 2 / 1",
+            "a?.if / b / c",
+            "let / 2 / 1",
+        ] {
+            let divisions = lex_types(input, Interner::new())
+                .into_iter()
+                .filter(|token| *token == TokenType::Operator(Operator::Div))
+                .count();
+            assert_eq!(divisions, input.matches('/').count(), "Lexing {input:?}");
+        }
+
+        // An operand follows `yield`, so a `/` begins a regular expression
+        let tokens = lex_types("yield /a/g", Interner::new());
+        assert_eq!(tokens[1], regex("a", "g"));
+    }
+
+    #[test]
+    fn lexer_uses_parser_supplied_input_element() {
+        let mut lexer = Lexer::new(")/a/");
+        lexer.next();
+        lexer.set_input_element(InputElement::RegExp);
+
+        let token = lexer.next().expect("Expected a token");
+        assert_eq!(
+            token.expect("Failed to lex regex").token_type,
+            regex("a", "")
+        )
+    }
+
+    #[test]
+    fn lexer_rejects_bad_regex() {
+        for (input, kind) in [
+            ("/abc", LexErrorKind::UnterminatedRegExp),
+            ("/a\n/", LexErrorKind::UnterminatedRegExp),
+            ("/a/gg", LexErrorKind::InvalidRegExpFlags("gg".to_string())),
+            ("/a/x", LexErrorKind::InvalidRegExpFlags("x".to_string())),
+        ] {
+            let mut lexer = Lexer::new(input);
+            let err = lexer.collect_regex().expect_err("Regex should be rejected");
+            assert_eq!(err.kind, kind);
+        }
+    }
+}
@@ -1,57 +1,209 @@
-//! Lexer implementations for tokenizing an entire string
+//! Lexer implementations for tokenizing an entire string, along with decoding the escape
+//! sequences shared by strings and template literals
 
 use crate::token::{Token, TokenType};
 
-use super::Lexer;
+use super::{char_class::is_line_terminator, LexError, LexErrorKind, Lexer, Result};
 
-impl Lexer {
-    /// Reads a complete string within two quote marks
-    pub(crate) fn collect_string(&mut self) -> Token {
-        let start = self.column;
-        let mut result = String::new();
+impl<'src> Lexer<'src> {
+    /// Reads a complete string within two matching quote marks, decoding any escape sequences.
+    /// An invalid escape sequence is only reported once the rest of the string has been read
+    pub(crate) fn collect_string(&mut self) -> Result<Token<'src>> {
+        let start = self.mark();
+        let quote = self.current_char();
         self.advance();
+        let mut cooked = self.text_builder();
         let mut closed = false;
+        let mut invalid_escape = None;
 
         while let Some(c) = self.current_char() {
-            match *c {
-                '"' => {
-                    self.advance();
-                    closed = true;
-                    break;
-                }
+            if Some(c) == quote {
+                cooked.include(self.pos);
+                self.advance();
+                closed = true;
+                break;
+            }
+
+            match c {
                 '\\' => {
+                    cooked.include(self.pos);
+                    let escape_column = self.column;
                     self.advance();
-                    if let Some(escaped) = self.current_char() {
-                        result.push(*escaped)
+                    match self.collect_escape() {
+                        Ok(escaped) => cooked.replace(escaped, self.pos),
+                        Err(kind) => {
+                            invalid_escape.get_or_insert(LexError::new(
+                                kind,
+                                self.line,
+                                escape_column,
+                            ));
+                        }
                     }
+                }
+                '\n' | '\r' => {
+                    return Err(invalid_escape.unwrap_or(LexError::new(
+                        LexErrorKind::LineTerminatorInString,
+                        self.line,
+                        self.column,
+                    )))
+                }
+                _ => self.advance(),
+            }
+        }
+
+        if !closed {
+            return Err(invalid_escape.unwrap_or(LexError::new(
+                LexErrorKind::UnterminatedString,
+                start.line,
+                start.column,
+            )));
+        }
+        if let Some(err) = invalid_escape {
+            return Err(err);
+        }
+
+        let token_type = TokenType::String {
+            cooked: cooked.finish(),
+            raw: self.source_from(start.offset).into(),
+        };
+        Ok(self.token_from(start, token_type))
+    }
+
+    /// Decodes an escape sequence, starting just after its `\`. Returns the escaped character, or
+    /// `None` for a line continuation which contributes nothing to the value
+    ///
+    /// A `\u` escape naming half of a surrogate pair is combined with the other half when it
+    /// directly follows, and a lone surrogate becomes U+FFFD as it cannot be held in a `String`
+    pub(crate) fn collect_escape(&mut self) -> std::result::Result<Option<char>, LexErrorKind> {
+        let Some(c) = self.current_char() else {
+            return Err(LexErrorKind::UnterminatedString);
+        };
+        self.advance();
+
+        let escaped = match c {
+            '\r' => {
+                if self.current_char() == Some('\n') {
                     self.advance();
                 }
-                _ => {
-                    result.push(*c);
+                return Ok(None);
+            }
+            c if is_line_terminator(c) => return Ok(None),
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\u{b}',
+            '0' if !self.current_char().is_some_and(|c| c.is_ascii_digit()) => '\0',
+            '0'..='9' if self.goal.is_strict() => return Err(LexErrorKind::LegacyOctal),
+            '0'..='7' => self.collect_legacy_octal_escape(c),
+            'x' => {
+                let value = self.collect_hex_digits(2)?;
+                char::from_u32(value).ok_or(LexErrorKind::InvalidEscape)?
+            }
+            'u' => self.collect_unicode_escape_value()?,
+            other => other,
+        };
+
+        Ok(Some(escaped))
+    }
+
+    /// Decodes the rest of a legacy octal escape such as `\101`, given its first digit
+    fn collect_legacy_octal_escape(&mut self, first: char) -> char {
+        let mut value = first.to_digit(8).unwrap_or_default();
+        // Escapes starting 0-3 may have three digits, `\377`, while any others have at most two
+        let max_len = if value <= 3 { 3 } else { 2 };
+
+        for _ in 1..max_len {
+            match self.current_char().and_then(|c| c.to_digit(8)) {
+                Some(digit) => {
+                    value = value * 8 + digit;
                     self.advance();
                 }
+                None => break,
             }
         }
 
-        if !closed {
-            todo!("Return error for unclosed string")
+        char::from_u32(value).unwrap_or_default()
+    }
+
+    /// Decodes the value of a `\u` escape, starting just after the `u`. Supports both the
+    /// `\uXXXX` and `\u{X...}` forms
+    pub(crate) fn collect_unicode_escape_value(
+        &mut self,
+    ) -> std::result::Result<char, LexErrorKind> {
+        let value = self.collect_code_point()?;
+
+        if (0xD800..0xDC00).contains(&value) {
+            // A high surrogate, which is only meaningful when a low surrogate escape follows
+            let low = (0..6).map(|n| self.peek_nth(n)).collect::<Option<String>>();
+            if let Some(low) = low.and_then(|low| low.strip_prefix("\\u").map(str::to_string)) {
+                if let Ok(low @ 0xDC00..=0xDFFF) = u32::from_str_radix(&low, 16) {
+                    for _ in 0..6 {
+                        self.advance();
+                    }
+                    let combined = 0x10000 + ((value - 0xD800) << 10) + (low - 0xDC00);
+                    return char::from_u32(combined).ok_or(LexErrorKind::InvalidEscape);
+                }
+            }
+        }
+
+        Ok(char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Reads the code point of a `\u` escape without any surrogate handling
+    fn collect_code_point(&mut self) -> std::result::Result<u32, LexErrorKind> {
+        if self.current_char() != Some('{') {
+            return self.collect_hex_digits(4);
         }
 
-        Token::new(TokenType::String(result), self.line, start)
+        self.advance();
+        let mut value: u32 = 0;
+        let mut read_any = false;
+        while let Some(digit) = self.current_char().and_then(|c| c.to_digit(16)) {
+            value = value.saturating_mul(16).saturating_add(digit);
+            read_any = true;
+            self.advance();
+        }
+
+        if !read_any || value > 0x10FFFF || self.current_char() != Some('}') {
+            return Err(LexErrorKind::InvalidEscape);
+        }
+        self.advance();
+
+        Ok(value)
+    }
+
+    /// Reads exactly `count` hexadecimal digits
+    fn collect_hex_digits(&mut self, count: usize) -> std::result::Result<u32, LexErrorKind> {
+        let mut value = 0;
+        for _ in 0..count {
+            let digit = self
+                .current_char()
+                .and_then(|c| c.to_digit(16))
+                .ok_or(LexErrorKind::InvalidEscape)?;
+            value = value * 16 + digit;
+            self.advance();
+        }
+
+        Ok(value)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{lexer::Lexer, token::TokenType};
+    use crate::{
+        lexer::{LexErrorKind, Lexer},
+        token::TokenType,
+    };
 
     #[test]
     fn lexer_read_string_reads_entire_string() {
         let input = "\"Hello World!\"".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let string = lexer.collect_string();
-        if let TokenType::String(val) = string.token_type {
+        let string = lexer.collect_string().expect("Failed to lex string");
+        if let TokenType::String { cooked: val, .. } = string.token_type {
             assert_eq!("Hello World!", val)
         } else {
             panic!("Token type was not a string")
@@ -61,13 +213,73 @@ mod tests {
     #[test]
     fn lexer_read_string_with_escapes() {
         let input = "\"Hello \'World!\'\"".to_string();
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(&input);
 
-        let string = lexer.collect_string();
-        if let TokenType::String(val) = string.token_type {
+        let string = lexer.collect_string().expect("Failed to lex string");
+        if let TokenType::String { cooked: val, .. } = string.token_type {
             assert_eq!("Hello 'World!'", val)
         } else {
             panic!("Token type was not a string")
         }
     }
+
+    #[test]
+    fn lexer_read_string_reports_unclosed_string() {
+        let input = "\"Hello World!".to_string();
+        let mut lexer = Lexer::new(&input);
+
+        let err = lexer
+            .collect_string()
+            .expect_err("String should not be closed");
+        assert_eq!(err.kind, LexErrorKind::UnterminatedString);
+    }
+
+    fn lex_string(input: &str) -> (String, String) {
+        let mut lexer = Lexer::new(input);
+        match lexer
+            .collect_string()
+            .expect("Failed to lex string")
+            .token_type
+        {
+            TokenType::String { cooked, raw } => (cooked.into_owned(), raw.into_owned()),
+            _ => panic!("Token type was not a string"),
+        }
+    }
+
+    #[test]
+    fn lexer_read_string_decodes_escapes() {
+        let (cooked, raw) = lex_string(r#""a\nb\t\x41B\u{1F600}\0\'\"""#);
+        assert_eq!("a\nb\tAB\u{1F600}\0'\"", cooked);
+        assert_eq!(r#""a\nb\t\x41B\u{1F600}\0\'\"""#, raw);
+
+        assert_eq!("\u{1F600}", lex_string(r"'😀'").0);
+        assert_eq!("ab", lex_string("'a\\\nb'").0);
+        assert_eq!("A\u{7}9\u{1}", lex_string(r"'\101\79\1'").0);
+    }
+
+    #[test]
+    fn lexer_read_string_matches_quote_style() {
+        assert_eq!("say \"hi\"", lex_string(r#"'say "hi"'"#).0);
+        assert_eq!("it's", lex_string(r#""it's""#).0);
+    }
+
+    #[test]
+    fn lexer_read_string_rejects_bad_escapes() {
+        for input in [r"'\x4'", r"'\u12'", r"'\u{110000}'", r"'\u{}'"] {
+            let mut lexer = Lexer::new(input);
+            let err = lexer
+                .collect_string()
+                .expect_err("Escape should be rejected");
+            assert_eq!(err.kind, LexErrorKind::InvalidEscape);
+        }
+    }
+
+    #[test]
+    fn lexer_read_string_rejects_line_terminators() {
+        let mut lexer = Lexer::new("'a\nb'");
+        let err = lexer
+            .collect_string()
+            .expect_err("Newline should be rejected");
+        assert_eq!(err.kind, LexErrorKind::LineTerminatorInString);
+    }
 }
@@ -0,0 +1,233 @@
+//! Lexer implementation for template literals and the substitutions within them
+
+use crate::token::{Punctuation, TemplatePart, Token, TokenType};
+
+use super::{LexError, LexErrorKind, Lexer, Result};
+
+/// What opened a `{` that has not been closed yet
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Brace {
+    /// A regular `{`, such as a block or object literal
+    Block,
+    /// The `${` of a template substitution, whose `}` continues the template
+    Substitution,
+}
+
+impl<'src> Lexer<'src> {
+    /// Whether the next `}` closes a template substitution rather than a block
+    pub(crate) fn in_substitution(&self) -> bool {
+        self.braces.last() == Some(&Brace::Substitution)
+    }
+
+    /// Keeps track of nested braces so the `}` ending a substitution can be told apart from one
+    /// closing a block within that substitution
+    pub(crate) fn track_braces(&mut self, token: &Token<'src>) {
+        match token.token_type {
+            TokenType::Punctuation(Punctuation::OpenSquiggle) => self.braces.push(Brace::Block),
+            TokenType::Punctuation(Punctuation::CloseSquiggle) => {
+                self.braces.pop();
+            }
+            _ => {}
+        }
+    }
+
+    /// Collects a section of a template literal. Starting on a `` ` `` this reads either a whole
+    /// template without substitutions or the head before the first `${`. Starting on the `}` that
+    /// ends a substitution it reads the middle up to the next `${` or the tail up to the closing
+    /// `` ` ``
+    pub(crate) fn collect_template(&mut self) -> Result<Token<'src>> {
+        let start = self.mark();
+        let continuation = self.current_char() == Some('}');
+        if continuation {
+            self.braces.pop();
+        }
+        self.advance();
+
+        let mut cooked = Some(self.text_builder());
+        let mut raw = self.text_builder();
+
+        while let Some(c) = self.current_char() {
+            match c {
+                '`' | '$' if c == '`' || self.peek_char() == Some('{') => {
+                    let cooked = cooked.map(|mut cooked| {
+                        cooked.include(self.pos);
+                        cooked.finish()
+                    });
+                    raw.include(self.pos);
+                    let raw = raw.finish();
+
+                    self.advance();
+                    let part = if c == '`' {
+                        if continuation {
+                            TemplatePart::Tail
+                        } else {
+                            TemplatePart::NoSubstitution
+                        }
+                    } else {
+                        self.advance();
+                        self.braces.push(Brace::Substitution);
+                        if continuation {
+                            TemplatePart::Middle
+                        } else {
+                            TemplatePart::Head
+                        }
+                    };
+
+                    return Ok(self.token_from(start, TokenType::Template { part, cooked, raw }));
+                }
+                '\\' => {
+                    // Legacy octal escapes other than `\0` are not allowed in templates
+                    let legacy_octal = match self.peek_nth(1) {
+                        Some('0') => self.peek_nth(2).is_some_and(|c| c.is_ascii_digit()),
+                        Some(c) => c.is_ascii_digit(),
+                        None => false,
+                    };
+                    if let Some(cooked) = &mut cooked {
+                        cooked.include(self.pos);
+                    }
+                    self.advance();
+
+                    let escaped = self.collect_escape();
+                    cooked = match (cooked, escaped) {
+                        (Some(mut cooked), Ok(escaped)) if !legacy_octal => {
+                            cooked.replace(escaped, self.pos);
+                            Some(cooked)
+                        }
+                        _ => None,
+                    };
+
+                    // Line continuations are part of the raw value, with their line ending
+                    // normalized like any other
+                    let escape = self.source_from(raw.end);
+                    if escape.contains('\r') {
+                        let normalized = escape.replace("\r\n", "\n").replace('\r', "\n");
+                        for c in normalized.chars() {
+                            raw.replace(Some(c), self.pos);
+                        }
+                    } else {
+                        raw.include(self.pos);
+                    }
+                }
+                '\r' => {
+                    // Both the cooked and raw values normalize CRLF and CR line endings to LF
+                    if let Some(cooked) = &mut cooked {
+                        cooked.include(self.pos);
+                    }
+                    raw.include(self.pos);
+
+                    self.advance();
+                    if self.current_char() == Some('\n') {
+                        self.advance();
+                    }
+
+                    raw.replace(Some('\n'), self.pos);
+                    if let Some(cooked) = &mut cooked {
+                        cooked.replace(Some('\n'), self.pos)
+                    }
+                }
+                _ => self.advance(),
+            }
+        }
+
+        Err(LexError::new(
+            LexErrorKind::UnterminatedTemplate,
+            start.line,
+            start.column,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lexer::{LexErrorKind, Lexer},
+        symbol::interner_with,
+        token::{Operator, Punctuation, TemplatePart, TokenType},
+    };
+
+    fn template(part: TemplatePart, text: &str) -> TokenType<'_> {
+        TokenType::Template {
+            part,
+            cooked: Some(text.into()),
+            raw: text.into(),
+        }
+    }
+
+    #[test]
+    fn lexer_collects_template_without_substitutions() {
+        let input = "`Hello\nWorld!`".to_string();
+        let mut lexer = Lexer::new(&input);
+
+        let token = lexer.collect_template().expect("Failed to lex template");
+        assert_eq!(
+            token.token_type,
+            template(TemplatePart::NoSubstitution, "Hello\nWorld!")
+        )
+    }
+
+    #[test]
+    fn lexer_collects_template_substitutions() {
+        let (interner, [b, e]) = interner_with(["b", "e"]);
+        let input = "`a${ {b: 1}.b }c${`d${e}`}f`".to_string();
+        let lexer = Lexer::new(&input).with_interner(interner);
+
+        let tokens: Vec<_> = lexer
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
+
+        let should_be = vec![
+            template(TemplatePart::Head, "a"),
+            TokenType::Punctuation(Punctuation::OpenSquiggle),
+            TokenType::Identifier(b),
+            TokenType::Punctuation(Punctuation::Colon),
+            TokenType::Number(1.0),
+            TokenType::Punctuation(Punctuation::CloseSquiggle),
+            TokenType::Operator(Operator::Dot),
+            TokenType::Identifier(b),
+            template(TemplatePart::Middle, "c"),
+            template(TemplatePart::Head, "d"),
+            TokenType::Identifier(e),
+            template(TemplatePart::Tail, ""),
+            template(TemplatePart::Tail, "f"),
+        ];
+
+        assert_eq!(tokens, should_be)
+    }
+
+    #[test]
+    fn lexer_reports_unclosed_template() {
+        let input = "`a${b}c".to_string();
+        let lexer = Lexer::new(&input);
+
+        let err = lexer
+            .collect::<Result<Vec<_>, _>>()
+            .expect_err("Template should not be closed");
+        assert_eq!(err.kind, LexErrorKind::UnterminatedTemplate);
+    }
+
+    #[test]
+    fn lexer_collects_template_escapes() {
+        let input = r"`\nA\`${x}\unicode`".to_string();
+        let tokens: Vec<_> = Lexer::new(&input)
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
+
+        assert_eq!(
+            tokens[0],
+            TokenType::Template {
+                part: TemplatePart::Head,
+                cooked: Some("\nA`".into()),
+                raw: r"\nA\`".into(),
+            }
+        );
+        // Invalid escapes are allowed in templates, but leave them without a cooked value
+        assert_eq!(
+            tokens[2],
+            TokenType::Template {
+                part: TemplatePart::Tail,
+                cooked: None,
+                raw: r"\unicode".into(),
+            }
+        );
+    }
+}
@@ -1,13 +1,63 @@
 //! Whitespace skipping and comment ignoring implementations for the lexer
 
-use super::Lexer;
+use crate::{
+    span::Span,
+    token::{Trivia, TriviaKind},
+};
 
-impl Lexer {
-    /// Skips whitespace until we are no longer at a whitespace char
+use super::{
+    char_class::{is_line_terminator, is_whitespace, BOM},
+    LexError, LexErrorKind, Lexer, Position, Result,
+};
+
+impl<'src> Lexer<'src> {
+    /// Enables recording of whitespace and comments, which are otherwise thrown away. Each is
+    /// kept as [`Trivia`] in a side table ordered by position, see [`Lexer::trivia`]
+    pub fn with_trivia(mut self) -> Self {
+        let mut trivia = vec![];
+        if self.pos > 0 {
+            // The skipped byte order mark
+            trivia.push(Trivia::new(TriviaKind::Whitespace, Span::new(0, self.pos)));
+        }
+
+        self.trivia = Some(trivia);
+        self
+    }
+
+    /// All whitespace and comments recorded so far, in source order. Always empty unless the
+    /// lexer was created [`Lexer::with_trivia`]
+    pub fn trivia(&self) -> &[Trivia] {
+        self.trivia.as_deref().unwrap_or_default()
+    }
+
+    /// Takes all whitespace and comments recorded so far, leaving trivia recording enabled
+    pub fn take_trivia(&mut self) -> Vec<Trivia> {
+        self.trivia.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Records a piece of trivia from `start` up to the current position, if trivia is enabled
+    fn record_trivia(&mut self, kind: TriviaKind, start: Position) {
+        let span = Span::new(start.offset, self.pos);
+        if let Some(trivia) = &mut self.trivia {
+            trivia.push(Trivia::new(kind, span));
+        }
+    }
+
+    /// Skips whitespace and line terminators until we are no longer at a whitespace char
     pub(crate) fn skip_whitespace(&mut self) {
         while let Some(c) = self.current_char() {
-            if c.is_whitespace() {
-                self.advance()
+            let start = self.mark();
+            if is_line_terminator(c) {
+                if c == '\r' && self.peek_char() == Some('\n') {
+                    self.advance();
+                }
+                self.advance();
+                self.record_trivia(TriviaKind::LineTerminator, start);
+            } else if is_whitespace(c) {
+                while self.current_char().is_some_and(is_whitespace) {
+                    self.advance();
+                }
+                self.record_trivia(TriviaKind::Whitespace, start);
             } else {
                 break;
             }
@@ -16,28 +66,182 @@ impl Lexer {
 
     /// Skips a single line comment
     pub(crate) fn skip_single_line_comment(&mut self) {
+        self.skip_rest_of_line(TriviaKind::LineComment);
+    }
+
+    /// Skips a `#!` comment, such as `#!/usr/bin/env scriptkiddie`
+    pub(crate) fn skip_hashbang_comment(&mut self) {
+        self.skip_rest_of_line(TriviaKind::Hashbang);
+    }
+
+    /// Whether a `#!` comment starts here, which is only allowed at the very start of the input
+    pub(crate) fn at_hashbang_comment(&self) -> bool {
+        let start = if self.source.starts_with(BOM) {
+            BOM.len_utf8()
+        } else {
+            0
+        };
+        self.pos == start && self.remaining().starts_with("#!")
+    }
+
+    /// Whether an Annex B HTML-like comment starts here. `<!--` begins a comment anywhere, while
+    /// `-->` only does so at the start of a line. Modules have no HTML-like comments
+    pub(crate) fn at_html_comment(&self) -> bool {
+        let remaining = self.remaining();
+        !self.goal.is_module()
+            && (remaining.starts_with("<!--")
+                || (self.newline_before && remaining.starts_with("-->")))
+    }
+
+    /// Skips everything up to the next line terminator, recording it as trivia of some kind
+    fn skip_rest_of_line(&mut self, kind: TriviaKind) {
+        let start = self.mark();
         while let Some(c) = self.current_char() {
-            if c == &'\n' {
+            if is_line_terminator(c) {
                 break;
             }
             self.advance()
         }
+        self.record_trivia(kind, start);
     }
 
-    /// Skips a multiline comment
-    pub(crate) fn skip_multi_line_comment(&mut self) {
+    /// Skips a multiline comment, failing if the comment is never closed
+    pub(crate) fn skip_multi_line_comment(&mut self) -> Result<()> {
+        let start = self.mark();
         // Skip the initial "/*"
         self.advance();
         self.advance();
 
         while let Some(c) = self.current_char() {
-            if c == &'*' && self.peek_char() == Some(&'/') {
+            if c == '*' && self.peek_char() == Some('/') {
                 self.advance();
                 self.advance();
-                break;
+                self.record_trivia(TriviaKind::BlockComment, start);
+                return Ok(());
             }
 
             self.advance()
         }
+
+        Err(LexError::new(
+            LexErrorKind::UnterminatedBlockComment,
+            start.line,
+            start.column,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lexer::{goal::Goal, Lexer},
+        span::Span,
+        symbol::interner_with,
+        token::{Operator, TokenType, Trivia, TriviaKind},
+    };
+
+    #[test]
+    fn lexer_skips_unicode_whitespace() {
+        let input = "\u{FEFF}a\u{A0}\u{3000}b\u{2028}c // comment\u{2029}d".to_string();
+        let mut lexer = Lexer::new(&input);
+        let tokens: Vec<_> = lexer
+            .by_ref()
+            .map(|token| token.expect("Failed to lex token"))
+            .collect();
+
+        let names: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| token.token_type.as_identifier(Goal::Script))
+            .map(|name| lexer.interner().resolve(name))
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c", "d"]);
+        assert_eq!(tokens[0].column, 1);
+        assert_eq!(tokens[2].line, 2);
+        assert_eq!(tokens[3].line, 3);
+    }
+
+    #[test]
+    fn lexer_counts_crlf_as_one_line() {
+        let input = "a\r\nb\rc".to_string();
+        let lines: Vec<_> = Lexer::new(&input)
+            .map(|token| token.expect("Failed to lex token").line)
+            .collect();
+
+        assert_eq!(lines, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn lexer_discards_trivia_by_default() {
+        let mut lexer = Lexer::new("a // comment\nb");
+        lexer.by_ref().for_each(drop);
+
+        assert!(lexer.trivia().is_empty());
+    }
+
+    #[test]
+    fn lexer_records_trivia() {
+        let input = "/** Docs */\r\nlet a; // note\n".to_string();
+        let mut lexer = Lexer::new(&input).with_trivia();
+        let tokens: Vec<_> = lexer
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("Failed to lex");
+
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(
+            lexer.trivia(),
+            [
+                Trivia::new(TriviaKind::BlockComment, Span::new(0, 11)),
+                Trivia::new(TriviaKind::LineTerminator, Span::new(11, 13)),
+                Trivia::new(TriviaKind::Whitespace, Span::new(16, 17)),
+                Trivia::new(TriviaKind::Whitespace, Span::new(19, 20)),
+                Trivia::new(TriviaKind::LineComment, Span::new(20, 27)),
+                Trivia::new(TriviaKind::LineTerminator, Span::new(27, 28)),
+            ]
+        );
+        assert_eq!(lexer.trivia()[0].span.source_text(&input), "/** Docs */");
+    }
+
+    #[test]
+    fn lexer_skips_hashbang_comment() {
+        let input = "#!/usr/bin/env scriptkiddie\nlet a;";
+        let mut lexer = Lexer::new(input).with_trivia();
+        let tokens: Vec<_> = lexer
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("Failed to lex");
+
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].line, 2);
+        assert_eq!(
+            lexer.trivia()[0],
+            Trivia::new(TriviaKind::Hashbang, Span::new(0, 27))
+        );
+
+        // Anywhere else `#!` is not a comment
+        let mut lexer = Lexer::new("a\n#!b");
+        assert!(lexer.nth(1).expect("Expected a token").is_err());
+    }
+
+    #[test]
+    fn lexer_skips_html_like_comments() {
+        let (interner, [a, e, f]) = interner_with(["a", "e", "f"]);
+        let input = "a <!-- b\n--> c\n /* x\n */ --> d\ne --> f";
+        let tokens: Vec<_> = Lexer::new(input)
+            .with_interner(interner)
+            .map(|token| token.expect("Failed to lex token").token_type)
+            .collect();
+
+        // `-->` partway through a line is a decrement and greater than
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Identifier(a),
+                TokenType::Identifier(e),
+                TokenType::Operator(Operator::Dec),
+                TokenType::Operator(Operator::Gt),
+                TokenType::Identifier(f),
+            ]
+        );
     }
 }
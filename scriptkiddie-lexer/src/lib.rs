@@ -3,4 +3,7 @@
 //! these tokens.
 
 pub mod lexer;
+pub mod printer;
+pub mod span;
+pub mod symbol;
 pub mod token;
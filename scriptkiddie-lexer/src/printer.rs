@@ -0,0 +1,185 @@
+//! Rendering tokens back into source text, either canonically or exactly as they were read
+
+use std::fmt::Write;
+
+use crate::{
+    symbol::Interner,
+    token::{Operator, Token, TokenType, Trivia},
+};
+
+/// Renders tokens as canonical source text using their [`display`](Token::display) form, with
+/// names resolved by the interner they were lexed with. Tokens that were separated in the source
+/// are separated by a single space, or a line break if one came between them, so the text reads
+/// back as the same tokens
+pub fn print_tokens(tokens: &[Token<'_>], interner: &Interner) -> String {
+    let mut printed = String::new();
+    let mut last: Option<&Token<'_>> = None;
+
+    for token in tokens {
+        if token.newline_before {
+            printed.push('\n');
+        } else if last.is_some_and(|last| {
+            last.span.end < token.span.start || would_merge(&last.token_type, &token.token_type)
+        }) {
+            printed.push(' ');
+        }
+
+        write!(printed, "{}", token.display(interner)).expect("Writing to a string cannot fail");
+        last = Some(token);
+    }
+
+    printed
+}
+
+/// Whether two tokens that touched in the source would read back as something else if printed
+/// together. Numbers are printed without the `.` or digits they may have been written with, so
+/// `1..a` or `0x10.a` would otherwise come out as `1.a` and `16.a`
+fn would_merge(first: &TokenType<'_>, second: &TokenType<'_>) -> bool {
+    match (first, second) {
+        (TokenType::Number(_), TokenType::Number(_)) => true,
+        (TokenType::Number(num), TokenType::Operator(Operator::Dot)) => {
+            num.is_finite() && num.fract() == 0.0
+        }
+        _ => false,
+    }
+}
+
+/// Reproduces the source text exactly from tokens and the trivia recorded between them, as given
+/// by a lexer [`with_trivia`](crate::lexer::Lexer::with_trivia). Every token and piece of trivia
+/// is written out as the source text its span covers, so any part of the source the lexer failed
+/// to account for is missing from the result
+pub fn print_lossless(source: &str, tokens: &[Token<'_>], trivia: &[Trivia]) -> String {
+    let mut printed = String::with_capacity(source.len());
+    let mut tokens = tokens.iter().peekable();
+    let mut trivia = trivia.iter().peekable();
+
+    loop {
+        // Both are in source order, so the next piece is whichever starts first
+        let span = match (tokens.peek(), trivia.peek()) {
+            (Some(token), Some(piece)) if piece.span.start < token.span.start => {
+                trivia.next().map(|piece| piece.span)
+            }
+            (Some(_), _) => tokens.next().map(|token| token.span),
+            (None, Some(_)) => trivia.next().map(|piece| piece.span),
+            (None, None) => None,
+        };
+        let Some(span) = span else {
+            break;
+        };
+
+        printed.push_str(span.source_text(source));
+    }
+
+    printed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::{
+        lexer::{goal::Goal, Lexer},
+        symbol::lex_types,
+    };
+
+    use super::{print_lossless, print_tokens};
+
+    /// Inputs covering every kind of token and trivia
+    const INPUTS: &[&str] = &[
+        "\u{feff}#!/usr/bin/env node\nlet a = 1;",
+        "const s = 'it\\'s' + \"\\u{1F600}\" // done\r\n",
+        "x = `a${ {b: `c${d}e`} }f` / 2 /g/i.test(y)",
+        "class A { #n = 0x1F; get n() { return this.#n ** 2n } }",
+        "a\n<!-- old\n--> comment\nb /* multi\nline */ ?. c ?? .5e3",
+        "f(...args) =>\u{2028}\u{3000}{ label: while (i --> 0) i >>>= 1 }",
+        "1..a + 0x10.a + 1.0.toString() + 0b11.toString() + 1..5 + 1.5.5",
+        r"\u0061sync + \u006ff + implements",
+    ];
+
+    /// Reads every JavaScript file the tests use
+    fn test_files() -> Vec<String> {
+        ["../test/simple.js", "../test/functions.js"]
+            .iter()
+            .map(|path| {
+                let mut text = String::new();
+                std::fs::File::open(path)
+                    .and_then(|mut file| file.read_to_string(&mut text))
+                    .expect("Failed to read file");
+                text
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tokens_display_as_source() {
+        let mut lexer = Lexer::new(r"/[/]/u #x 0x10 1e400 7n `a${ }b${ }c` \u0061 'q'");
+        let tokens: Vec<_> = lexer
+            .by_ref()
+            .map(|token| token.expect("Failed to lex token"))
+            .collect();
+        let rendered: Vec<_> = tokens
+            .iter()
+            .map(|token| token.display(lexer.interner()).to_string())
+            .collect();
+
+        assert_eq!(
+            rendered,
+            vec!["/[/]/u", "#x", "16", "1e400", "7n", "`a${", "}b${", "}c`", "a", "'q'"]
+        );
+    }
+
+    #[test]
+    fn printed_tokens_read_back_the_same() {
+        let files = test_files();
+        for input in INPUTS
+            .iter()
+            .copied()
+            .chain(files.iter().map(String::as_str))
+        {
+            let mut lexer = Lexer::new(input);
+            let tokens: Vec<_> = lexer
+                .by_ref()
+                .map(|token| token.expect("Failed to lex token"))
+                .collect();
+            let printed = print_tokens(&tokens, lexer.interner());
+
+            let expected: Vec<_> = tokens.into_iter().map(|token| token.token_type).collect();
+            let interner = lexer.into_interner();
+            assert_eq!(
+                lex_types(&printed, interner),
+                expected,
+                "Printed {printed:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn lossless_printing_reproduces_the_input() {
+        let files = test_files();
+        for input in INPUTS
+            .iter()
+            .copied()
+            .chain(files.iter().map(String::as_str))
+        {
+            let mut lexer = Lexer::new(input).with_trivia();
+            let tokens: Vec<_> = lexer
+                .by_ref()
+                .collect::<Result<_, _>>()
+                .expect("Failed to lex input");
+
+            assert_eq!(print_lossless(input, &tokens, lexer.trivia()), input);
+        }
+
+        // Invalid input is covered by its token
+        let input = "a @ 'b\nc";
+        let mut lexer = Lexer::new(input)
+            .with_trivia()
+            .with_recovery()
+            .with_goal(Goal::Module);
+        let tokens: Vec<_> = lexer
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("Recovery should not fail");
+        assert_eq!(print_lossless(input, &tokens, lexer.trivia()), input);
+    }
+}
@@ -0,0 +1,171 @@
+//! Source ranges for tokens, and a way to turn them back into lines and columns
+
+/// A range of byte offsets into the source text, from `start` up to but excluding `end`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// The offset of the first byte
+    pub start: usize,
+    /// The offset just past the last byte
+    pub end: usize,
+}
+
+impl Span {
+    /// Creates a new span between two byte offsets
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// The number of bytes the span covers
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the span covers no bytes at all
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The source text the span covers
+    pub fn source_text<'src>(&self, source: &'src str) -> &'src str {
+        &source[self.start..self.end]
+    }
+}
+
+/// The unit columns are counted in. Editors and tools disagree on this, so positions handed to
+/// them should be counted the way they expect
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PositionEncoding {
+    /// Bytes of UTF-8, matching offsets into a Rust string
+    Utf8,
+    /// UTF-16 code units, as used by browsers, source maps and the Language Server Protocol
+    Utf16,
+    /// Unicode code points, one per Rust `char`
+    #[default]
+    CodePoints,
+}
+
+impl PositionEncoding {
+    /// How many columns a character takes up
+    pub fn width(self, c: char) -> usize {
+        match self {
+            PositionEncoding::Utf8 => c.len_utf8(),
+            PositionEncoding::Utf16 => c.len_utf16(),
+            PositionEncoding::CodePoints => 1,
+        }
+    }
+}
+
+/// An index of where every line of some source text begins, used to find the line and column of
+/// a byte offset without rescanning the source
+pub struct LineIndex<'src> {
+    /// The source text being indexed
+    source: &'src str,
+    /// The byte offset each line starts at, in order
+    line_starts: Vec<usize>,
+}
+
+impl<'src> LineIndex<'src> {
+    /// Indexes the lines of some source text. A line ends at a LF, CR, CRLF, U+2028 or U+2029
+    pub fn new(source: &'src str) -> Self {
+        let mut line_starts = vec![0];
+        let mut chars = source.char_indices().peekable();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\r' if chars.peek().is_some_and(|(_, next)| *next == '\n') => {}
+                '\n' | '\r' | '\u{2028}' | '\u{2029}' => line_starts.push(offset + c.len_utf8()),
+                _ => {}
+            }
+        }
+
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// The number of lines in the source
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The 1-based line and column of a byte offset, where the column counts characters. See
+    /// [`LineIndex::line_column_in`] for offsets that are not within the source
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        self.line_column_in(offset, PositionEncoding::CodePoints)
+    }
+
+    /// The 1-based line and column of a byte offset, with the column counted in some encoding.
+    /// The offset should be a char boundary within the source. An offset past the end is treated
+    /// as the end, and one partway through a char as the start of that char
+    pub fn line_column_in(&self, offset: usize, encoding: PositionEncoding) -> (usize, usize) {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.source[line_start..offset]
+            .chars()
+            .map(|c| encoding.width(c))
+            .sum::<usize>()
+            + 1;
+
+        (line, column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineIndex, PositionEncoding, Span};
+
+    #[test]
+    fn span_source_text() {
+        let source = "let café = 1;";
+        assert_eq!(Span::new(4, 9).source_text(source), "café");
+        assert_eq!(Span::new(4, 9).len(), 5);
+    }
+
+    #[test]
+    fn line_index_finds_lines_and_columns() {
+        let source = "ab\ncé\r\nd\u{2028}e";
+        let index = LineIndex::new(source);
+
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line_column(0), (1, 1));
+        assert_eq!(index.line_column(1), (1, 2));
+        assert_eq!(index.line_column(3), (2, 1));
+        // `é` is two bytes, so `\r` starts at offset 6
+        assert_eq!(index.line_column(6), (2, 3));
+        assert_eq!(index.line_column(8), (3, 1));
+        assert_eq!(index.line_column(12), (4, 1));
+    }
+
+    #[test]
+    fn line_index_counts_columns_in_an_encoding() {
+        let source = "a\n😀é x";
+        let index = LineIndex::new(source);
+
+        assert_eq!(index.line_column_in(9, PositionEncoding::Utf8), (2, 8));
+        assert_eq!(index.line_column_in(9, PositionEncoding::Utf16), (2, 5));
+        assert_eq!(
+            index.line_column_in(9, PositionEncoding::CodePoints),
+            (2, 4)
+        );
+    }
+
+    #[test]
+    fn line_index_clamps_offsets() {
+        let source = "a
+cé";
+        let index = LineIndex::new(source);
+
+        // Partway through `é` is the start of it
+        assert_eq!(index.line_column(4), (2, 2));
+        assert_eq!(index.line_column(5), (2, 3));
+        assert_eq!(index.line_column(100), (2, 3));
+        assert_eq!(LineIndex::new("").line_column(1), (1, 1));
+    }
+}
@@ -0,0 +1,172 @@
+//! Interned names, so identifiers can be compared and hashed as integers
+
+use std::{collections::HashMap, sync::Arc};
+
+use crate::token::Keyword;
+
+/// A table of interned strings. A lexer interns names into its own, which is then handed on to
+/// the parser and executor working on the same code so they all agree on what each [`Symbol`]
+/// means. The strings are freed along with the interner
+#[derive(Clone, Debug)]
+pub struct Interner {
+    /// The symbol each string was given
+    symbols: HashMap<Arc<str>, Symbol>,
+    /// The string behind each symbol, indexed by the symbol
+    strings: Vec<Arc<str>>,
+}
+
+impl Interner {
+    /// Creates an interner holding only the keywords, which always have the same symbols
+    pub fn new() -> Self {
+        let mut interner = Self {
+            symbols: HashMap::new(),
+            strings: Vec::new(),
+        };
+        for keyword in Keyword::ALL {
+            interner.intern(keyword.as_str());
+        }
+
+        interner
+    }
+
+    /// Finds the symbol for a string, interning it if it has not been seen before
+    pub fn intern(&mut self, string: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(string) {
+            return symbol;
+        }
+
+        let symbol = Symbol(u32::try_from(self.strings.len()).expect("Too many interned strings"));
+        let string: Arc<str> = string.into();
+        self.strings.push(string.clone());
+        self.symbols.insert(string, symbol);
+        symbol
+    }
+
+    /// The symbol for a string if it has been interned, without interning it
+    pub fn get(&self, string: &str) -> Option<Symbol> {
+        self.symbols.get(string).copied()
+    }
+
+    /// The string a symbol was interned from. The symbol must have come from this interner, or
+    /// one it was cloned from
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.0 as usize]
+    }
+
+    /// The number of strings interned, not counting the keywords
+    pub fn len(&self) -> usize {
+        self.strings.len() - Keyword::ALL.len()
+    }
+
+    /// Whether nothing but the keywords has been interned
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Only the strings past the keywords are written, in the order they were interned, so reading
+/// them back gives every symbol the same meaning
+#[cfg(feature = "serde")]
+impl serde::Serialize for Interner {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.strings[Keyword::ALL.len()..].iter().map(|s| &**s))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Interner {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let strings = <Vec<std::borrow::Cow<'de, str>>>::deserialize(deserializer)?;
+        let mut interner = Interner::new();
+        for string in strings {
+            interner.intern(&string);
+        }
+
+        Ok(interner)
+    }
+}
+
+/// A compact handle to a string in an [`Interner`]. Two symbols from the same interner are equal
+/// exactly when their strings are, so names can be compared without looking at their text
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// The index of this symbol within its interner
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+impl Keyword {
+    /// The symbol of the keyword's text, which is the same in every interner
+    pub fn symbol(&self) -> Symbol {
+        Symbol(self.clone() as u32)
+    }
+}
+
+/// Creates an interner holding some names, so tests can compare lexed names with their symbols
+#[cfg(test)]
+pub(crate) fn interner_with<const N: usize>(names: [&str; N]) -> (Interner, [Symbol; N]) {
+    let mut interner = Interner::new();
+    let symbols = names.map(|name| interner.intern(name));
+    (interner, symbols)
+}
+
+/// Lexes some input with an interner, panicking on any error, and keeps only the token types
+#[cfg(test)]
+pub(crate) fn lex_types(input: &str, interner: Interner) -> Vec<crate::token::TokenType<'_>> {
+    crate::lexer::Lexer::new(input)
+        .with_interner(interner)
+        .map(|token| token.expect("Failed to lex token").token_type)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::token::Keyword;
+
+    use super::Interner;
+
+    #[test]
+    fn interning_the_same_text_gives_the_same_symbol() {
+        let mut interner = Interner::new();
+        let a = interner.intern("counter");
+        let b = interner.intern(&String::from("counter"));
+
+        assert_eq!(a, b);
+        assert_eq!(a.as_u32(), b.as_u32());
+        assert_ne!(a, interner.intern("Counter"));
+        assert_eq!(interner.get("counter"), Some(a));
+        assert_eq!(interner.get("missing"), None);
+    }
+
+    #[test]
+    fn symbols_resolve_to_their_text() {
+        let mut interner = Interner::new();
+        let symbol = interner.intern("café");
+
+        assert_eq!(interner.resolve(symbol), "café");
+        assert_eq!(interner.clone().resolve(symbol), "café");
+    }
+
+    #[test]
+    fn keywords_have_the_same_symbol_in_every_interner() {
+        let mut interner = Interner::new();
+        assert!(interner.is_empty());
+
+        for keyword in Keyword::ALL {
+            assert_eq!(interner.intern(keyword.as_str()), keyword.symbol());
+            assert_eq!(interner.resolve(keyword.symbol()), keyword.as_str());
+        }
+        assert!(interner.is_empty());
+    }
+}
@@ -1,39 +1,285 @@
 //! All Token primatives and utilities wrapped around these primatives
 
-/// A contextual token with location and typing
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    lexer::{goal::Goal, LexErrorKind},
+    span::Span,
+    symbol::{Interner, Symbol},
+};
+
+/// A contextual token with location and typing. Any text the token holds is borrowed from the
+/// source it was read from where possible
 #[derive(Clone, Debug, PartialEq)]
-pub struct Token {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Token<'src> {
     /// The token's type
-    pub token_type: TokenType,
-    /// The token's line
+    pub token_type: TokenType<'src>,
+    /// The byte range of the source the token was read from
+    pub span: Span,
+    /// The line the token starts on
     pub line: usize,
-    /// The token's column
+    /// The column the token starts on, counted in the lexer's
+    /// [`PositionEncoding`](crate::span::PositionEncoding)
     pub column: usize,
+    /// Whether a line terminator comes between this token and the one before it, including one
+    /// within a comment. Needed for automatic semicolon insertion and restricted productions such
+    /// as `return\nx`
+    pub newline_before: bool,
 }
 
-impl Token {
-    pub fn new(token_type: TokenType, line: usize, column: usize) -> Self {
+impl<'src> Token<'src> {
+    /// Creates a new token covering `span`, which starts at a line and column
+    pub fn new(token_type: TokenType<'src>, span: Span, line: usize, column: usize) -> Self {
         Self {
             token_type,
+            span,
             line,
             column,
+            newline_before: false,
         }
     }
+
+    /// Copies any text borrowed from the source so the token can outlive it
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            token_type: self.token_type.into_owned(),
+            span: self.span,
+            line: self.line,
+            column: self.column,
+            newline_before: self.newline_before,
+        }
+    }
+
+    /// Shows the token as canonical source text, see [`TokenType::display`]
+    pub fn display<'a>(&'a self, interner: &'a Interner) -> DisplayToken<'a, 'src> {
+        self.token_type.display(interner)
+    }
+}
+
+/// Source text between tokens that carries no meaning for the parser
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trivia {
+    /// What sort of trivia this is
+    pub kind: TriviaKind,
+    /// The byte range of the source the trivia covers
+    pub span: Span,
+}
+
+impl Trivia {
+    /// Creates a new piece of trivia covering `span`
+    pub fn new(kind: TriviaKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+/// All kinds of trivia
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TriviaKind {
+    /// A run of whitespace within a single line
+    Whitespace,
+    /// A single line break, where CRLF counts as one
+    LineTerminator,
+    /// A `//` comment, or an HTML-like `<!--` or `-->` comment, not including the line
+    /// terminator that ends it
+    LineComment,
+    /// A `/* */` comment
+    BlockComment,
+    /// A `#!` comment at the very start of the input
+    Hashbang,
 }
 
 /// A single lexical token's type
 #[derive(Clone, Debug, PartialEq)]
-pub enum TokenType {
-    Identifier(String),
-    Number(f64),
-    String(String),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TokenType<'src> {
+    Identifier(Symbol),
+    /// A private class member name such as `#count`, stored without its `#`
+    PrivateName(Symbol),
+    /// Text between JSX tags, exactly as written
+    #[cfg(feature = "jsx")]
+    JsxText(Cow<'src, str>),
+    /// A JSX tag or attribute name, which unlike an identifier may contain `-`
+    #[cfg(feature = "jsx")]
+    JsxIdentifier(Symbol),
+    /// A number, serialized as a string such as `"Infinity"` when it is not finite
+    Number(#[cfg_attr(feature = "serde", serde(with = "number_serde"))] f64),
+    /// An arbitrary precision integer, stored as its decimal digits
+    BigInt(Cow<'src, str>),
+    /// A string literal
+    String {
+        /// The value of the string with escape sequences applied
+        cooked: Cow<'src, str>,
+        /// The literal exactly as written, including its quotes
+        raw: Cow<'src, str>,
+    },
+    /// A section of a template literal, split around its `${ }` substitutions
+    Template {
+        /// Which section of the template this is
+        part: TemplatePart,
+        /// The text with escape sequences applied, or `None` if it contains an invalid escape
+        /// sequence, which is only allowed in tagged templates
+        cooked: Option<Cow<'src, str>>,
+        /// The text exactly as written, excluding the delimiters
+        raw: Cow<'src, str>,
+    },
+    /// A regular expression literal, `/body/flags`
+    RegExp {
+        /// The pattern between the slashes
+        body: Cow<'src, str>,
+        /// The flags after the closing slash
+        flags: Cow<'src, str>,
+    },
     Keyword(Keyword),
     Operator(Operator),
     Punctuation(Punctuation),
+    /// Input that could not be lexed, only produced by a lexer
+    /// [`with_recovery`](crate::lexer::Lexer::with_recovery)
+    Invalid(LexErrorKind),
 }
 
-/// A keyword token's variants
+impl<'src> TokenType<'src> {
+    /// Copies any text borrowed from the source so the token type can outlive it
+    pub fn into_owned(self) -> TokenType<'static> {
+        self.map_text(|text| Cow::Owned(text.into_owned()))
+    }
+
+    /// Converts every piece of text the token type holds, keeping everything else
+    pub(crate) fn map_text<'a>(
+        self,
+        mut f: impl FnMut(Cow<'src, str>) -> Cow<'a, str>,
+    ) -> TokenType<'a> {
+        match self {
+            TokenType::Identifier(name) => TokenType::Identifier(name),
+            TokenType::PrivateName(name) => TokenType::PrivateName(name),
+            #[cfg(feature = "jsx")]
+            TokenType::JsxText(text) => TokenType::JsxText(f(text)),
+            #[cfg(feature = "jsx")]
+            TokenType::JsxIdentifier(name) => TokenType::JsxIdentifier(name),
+            TokenType::Number(num) => TokenType::Number(num),
+            TokenType::BigInt(digits) => TokenType::BigInt(f(digits)),
+            TokenType::String { cooked, raw } => TokenType::String {
+                cooked: f(cooked),
+                raw: f(raw),
+            },
+            TokenType::Template { part, cooked, raw } => TokenType::Template {
+                part,
+                cooked: cooked.map(&mut f),
+                raw: f(raw),
+            },
+            TokenType::RegExp { body, flags } => TokenType::RegExp {
+                body: f(body),
+                flags: f(flags),
+            },
+            TokenType::Keyword(keyword) => TokenType::Keyword(keyword),
+            TokenType::Operator(op) => TokenType::Operator(op),
+            TokenType::Punctuation(punc) => TokenType::Punctuation(punc),
+            TokenType::Invalid(reason) => TokenType::Invalid(reason),
+        }
+    }
+
+    /// The name this token has if it can be used as an identifier, such as a variable or function
+    /// name, in code with a certain goal. This is any plain identifier along with the keywords
+    /// not reserved there, so `await` is a name in a script but not in a module
+    pub fn as_identifier(&self, goal: Goal) -> Option<Symbol> {
+        match self {
+            TokenType::Identifier(name) => Some(*name),
+            TokenType::Keyword(keyword) if !keyword.is_reserved(goal) => Some(keyword.symbol()),
+            _ => None,
+        }
+    }
+
+    /// The name this token has if it can be used where any identifier name is allowed, such as
+    /// a property name after a `.`. Unlike [`TokenType::as_identifier`] this includes every
+    /// reserved word
+    pub fn identifier_name(&self) -> Option<Symbol> {
+        match self {
+            TokenType::Identifier(name) => Some(*name),
+            TokenType::Keyword(keyword) => Some(keyword.symbol()),
+            _ => None,
+        }
+    }
+
+    /// Shows the token type as canonical source text, with names resolved by the interner they
+    /// were interned into. This reads back as the same token, though not always as it was
+    /// written: escapes in names are applied and numbers are written in decimal. A name spelled
+    /// like a keyword, such as an escaped `\u0061sync`, keeps an escape on its first character
+    pub fn display<'a>(&'a self, interner: &'a Interner) -> DisplayToken<'a, 'src> {
+        DisplayToken {
+            token_type: self,
+            interner,
+        }
+    }
+}
+
+/// A token type shown as canonical source text, see [`TokenType::display`]
+pub struct DisplayToken<'a, 'src> {
+    /// The token type being shown
+    token_type: &'a TokenType<'src>,
+    /// The interner its names resolve with
+    interner: &'a Interner,
+}
+
+impl fmt::Display for DisplayToken<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |name: &Symbol| self.interner.resolve(*name);
+        match self.token_type {
+            TokenType::Identifier(symbol) => {
+                let name = name(symbol);
+                match name.chars().next() {
+                    // Unescaped, this would read back as the keyword
+                    Some(first) if Keyword::to_keyword(name).is_some() => {
+                        write!(f, "\\u{:04x}{}", first as u32, &name[first.len_utf8()..])
+                    }
+                    _ => f.write_str(name),
+                }
+            }
+            TokenType::PrivateName(symbol) => write!(f, "#{}", name(symbol)),
+            #[cfg(feature = "jsx")]
+            TokenType::JsxText(text) => f.write_str(text),
+            #[cfg(feature = "jsx")]
+            TokenType::JsxIdentifier(symbol) => f.write_str(name(symbol)),
+            // Any literal too large for a double stands in for infinity, which has no literal
+            TokenType::Number(num) if num.is_infinite() => f.write_str("1e400"),
+            TokenType::Number(num) => write!(f, "{num}"),
+            TokenType::BigInt(digits) => write!(f, "{digits}n"),
+            TokenType::String { raw, .. } => f.write_str(raw),
+            TokenType::Template { part, raw, .. } => match part {
+                TemplatePart::NoSubstitution => write!(f, "`{raw}`"),
+                TemplatePart::Head => write!(f, "`{raw}${{"),
+                TemplatePart::Middle => write!(f, "}}{raw}${{"),
+                TemplatePart::Tail => write!(f, "}}{raw}`"),
+            },
+            TokenType::RegExp { body, flags } => write!(f, "/{body}/{flags}"),
+            TokenType::Keyword(keyword) => write!(f, "{keyword}"),
+            TokenType::Operator(op) => write!(f, "{op}"),
+            TokenType::Punctuation(punc) => write!(f, "{punc}"),
+            // The text of invalid input is only known from the source, so say why it was invalid
+            TokenType::Invalid(reason) => write!(f, "/* {reason} */"),
+        }
+    }
+}
+
+/// The sections a template literal is split into
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TemplatePart {
+    /// A whole template with no substitutions, `` `text` ``
+    NoSubstitution,
+    /// The text before the first substitution, `` `text${ ``
+    Head,
+    /// The text between two substitutions, `}text${`
+    Middle,
+    /// The text after the last substitution, `` }text` ``
+    Tail,
+}
+
+/// A keyword token's variants. Covers every reserved word along with the contextual keywords
+/// that only have special meaning in certain places, see [`Keyword::is_contextual`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Keyword {
     Let,
     Const,
@@ -47,9 +293,101 @@ pub enum Keyword {
     Switch,
     Case,
     Break,
+    Do,
+    Continue,
+    New,
+    Delete,
+    Typeof,
+    Instanceof,
+    In,
+    This,
+    Null,
+    True,
+    False,
+    Class,
+    Extends,
+    Super,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    Default,
+    Import,
+    Export,
+    Void,
+    With,
+    Debugger,
+    Enum,
+    Yield,
+    Await,
+    Async,
+    Of,
+    Get,
+    Set,
+    Static,
+    Implements,
+    Interface,
+    Package,
+    Private,
+    Protected,
+    Public,
 }
 
 impl Keyword {
+    /// Every keyword, in the order they are declared
+    pub const ALL: [Keyword; 50] = [
+        Keyword::Let,
+        Keyword::Const,
+        Keyword::Var,
+        Keyword::For,
+        Keyword::Function,
+        Keyword::If,
+        Keyword::Else,
+        Keyword::While,
+        Keyword::Return,
+        Keyword::Switch,
+        Keyword::Case,
+        Keyword::Break,
+        Keyword::Do,
+        Keyword::Continue,
+        Keyword::New,
+        Keyword::Delete,
+        Keyword::Typeof,
+        Keyword::Instanceof,
+        Keyword::In,
+        Keyword::This,
+        Keyword::Null,
+        Keyword::True,
+        Keyword::False,
+        Keyword::Class,
+        Keyword::Extends,
+        Keyword::Super,
+        Keyword::Throw,
+        Keyword::Try,
+        Keyword::Catch,
+        Keyword::Finally,
+        Keyword::Default,
+        Keyword::Import,
+        Keyword::Export,
+        Keyword::Void,
+        Keyword::With,
+        Keyword::Debugger,
+        Keyword::Enum,
+        Keyword::Yield,
+        Keyword::Await,
+        Keyword::Async,
+        Keyword::Of,
+        Keyword::Get,
+        Keyword::Set,
+        Keyword::Static,
+        Keyword::Implements,
+        Keyword::Interface,
+        Keyword::Package,
+        Keyword::Private,
+        Keyword::Protected,
+        Keyword::Public,
+    ];
+
     /// Checks if a string is a valid keyword, if so, returns the keyword that it is
     pub fn to_keyword(check: &str) -> Option<Keyword> {
         match check {
@@ -61,17 +399,162 @@ impl Keyword {
             "if" => Some(Keyword::If),
             "else" => Some(Keyword::Else),
             "while" => Some(Keyword::While),
-            "switch" => Some(Keyword::Switch),
             "return" => Some(Keyword::Return),
-            "case" => Some(Keyword::Break),
+            "switch" => Some(Keyword::Switch),
+            "case" => Some(Keyword::Case),
+            "break" => Some(Keyword::Break),
+            "do" => Some(Keyword::Do),
+            "continue" => Some(Keyword::Continue),
+            "new" => Some(Keyword::New),
+            "delete" => Some(Keyword::Delete),
+            "typeof" => Some(Keyword::Typeof),
+            "instanceof" => Some(Keyword::Instanceof),
+            "in" => Some(Keyword::In),
+            "this" => Some(Keyword::This),
+            "null" => Some(Keyword::Null),
+            "true" => Some(Keyword::True),
+            "false" => Some(Keyword::False),
+            "class" => Some(Keyword::Class),
+            "extends" => Some(Keyword::Extends),
+            "super" => Some(Keyword::Super),
+            "throw" => Some(Keyword::Throw),
+            "try" => Some(Keyword::Try),
+            "catch" => Some(Keyword::Catch),
+            "finally" => Some(Keyword::Finally),
+            "default" => Some(Keyword::Default),
+            "import" => Some(Keyword::Import),
+            "export" => Some(Keyword::Export),
+            "void" => Some(Keyword::Void),
+            "with" => Some(Keyword::With),
+            "debugger" => Some(Keyword::Debugger),
+            "enum" => Some(Keyword::Enum),
+            "yield" => Some(Keyword::Yield),
+            "await" => Some(Keyword::Await),
+            "async" => Some(Keyword::Async),
+            "of" => Some(Keyword::Of),
+            "get" => Some(Keyword::Get),
+            "set" => Some(Keyword::Set),
+            "static" => Some(Keyword::Static),
+            "implements" => Some(Keyword::Implements),
+            "interface" => Some(Keyword::Interface),
+            "package" => Some(Keyword::Package),
+            "private" => Some(Keyword::Private),
+            "protected" => Some(Keyword::Protected),
+            "public" => Some(Keyword::Public),
 
             _ => None,
         }
     }
+
+    /// The keyword as it is written in source
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Keyword::Let => "let",
+            Keyword::Const => "const",
+            Keyword::Var => "var",
+            Keyword::For => "for",
+            Keyword::Function => "function",
+            Keyword::If => "if",
+            Keyword::Else => "else",
+            Keyword::While => "while",
+            Keyword::Return => "return",
+            Keyword::Switch => "switch",
+            Keyword::Case => "case",
+            Keyword::Break => "break",
+            Keyword::Do => "do",
+            Keyword::Continue => "continue",
+            Keyword::New => "new",
+            Keyword::Delete => "delete",
+            Keyword::Typeof => "typeof",
+            Keyword::Instanceof => "instanceof",
+            Keyword::In => "in",
+            Keyword::This => "this",
+            Keyword::Null => "null",
+            Keyword::True => "true",
+            Keyword::False => "false",
+            Keyword::Class => "class",
+            Keyword::Extends => "extends",
+            Keyword::Super => "super",
+            Keyword::Throw => "throw",
+            Keyword::Try => "try",
+            Keyword::Catch => "catch",
+            Keyword::Finally => "finally",
+            Keyword::Default => "default",
+            Keyword::Import => "import",
+            Keyword::Export => "export",
+            Keyword::Void => "void",
+            Keyword::With => "with",
+            Keyword::Debugger => "debugger",
+            Keyword::Enum => "enum",
+            Keyword::Yield => "yield",
+            Keyword::Await => "await",
+            Keyword::Async => "async",
+            Keyword::Of => "of",
+            Keyword::Get => "get",
+            Keyword::Set => "set",
+            Keyword::Static => "static",
+            Keyword::Implements => "implements",
+            Keyword::Interface => "interface",
+            Keyword::Package => "package",
+            Keyword::Private => "private",
+            Keyword::Protected => "protected",
+            Keyword::Public => "public",
+        }
+    }
+
+    /// Whether the keyword is contextual, meaning it is only special in certain positions and may
+    /// otherwise be used as an identifier, such as `of` in `for (x of y)` or `async` in
+    /// `let async = 1`
+    pub fn is_contextual(&self) -> bool {
+        matches!(
+            self,
+            Keyword::Let
+                | Keyword::Static
+                | Keyword::Yield
+                | Keyword::Await
+                | Keyword::Async
+                | Keyword::Of
+                | Keyword::Get
+                | Keyword::Set
+        )
+    }
+
+    /// Whether the keyword is reserved only in strict mode code, such as `implements` or `let`
+    pub fn is_strict_reserved(&self) -> bool {
+        matches!(
+            self,
+            Keyword::Implements
+                | Keyword::Interface
+                | Keyword::Let
+                | Keyword::Package
+                | Keyword::Private
+                | Keyword::Protected
+                | Keyword::Public
+                | Keyword::Static
+                | Keyword::Yield
+        )
+    }
+
+    /// Whether the keyword is a reserved word for code with a certain goal, meaning it can never
+    /// be used as an identifier there
+    pub fn is_reserved(&self, goal: Goal) -> bool {
+        match self {
+            Keyword::Await => goal.is_module(),
+            keyword if keyword.is_strict_reserved() => goal.is_strict(),
+            keyword => !keyword.is_contextual(),
+        }
+    }
+}
+
+impl fmt::Display for Keyword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// All operation types
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Operator {
     Assignment,
     Not,
@@ -94,10 +577,36 @@ pub enum Operator {
     ModAssign,
     Inc,
     Dec,
+    StrictEq,
+    StrictNe,
+    Exp,
+    ExpAssign,
+    Shl,
+    Shr,
+    UShr,
+    ShlAssign,
+    ShrAssign,
+    UShrAssign,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    BitAndAssign,
+    BitOrAssign,
+    BitXorAssign,
+    And,
+    Or,
+    Nullish,
+    AndAssign,
+    OrAssign,
+    NullishAssign,
+    OptionalChain,
+    Arrow,
+    Spread,
 }
 
 impl Operator {
-    /// Checks if a string is a valid keyword, if so, returns the keyword that it is
+    /// Checks if a string is a valid operator, if so, returns the operator that it is
     pub fn to_operator(check: &str) -> Option<Operator> {
         match check {
             "=" => Some(Operator::Assignment),
@@ -121,13 +630,99 @@ impl Operator {
             "%=" => Some(Operator::ModAssign),
             "++" => Some(Operator::Inc),
             "--" => Some(Operator::Dec),
+            "===" => Some(Operator::StrictEq),
+            "!==" => Some(Operator::StrictNe),
+            "**" => Some(Operator::Exp),
+            "**=" => Some(Operator::ExpAssign),
+            "<<" => Some(Operator::Shl),
+            ">>" => Some(Operator::Shr),
+            ">>>" => Some(Operator::UShr),
+            "<<=" => Some(Operator::ShlAssign),
+            ">>=" => Some(Operator::ShrAssign),
+            ">>>=" => Some(Operator::UShrAssign),
+            "&" => Some(Operator::BitAnd),
+            "|" => Some(Operator::BitOr),
+            "^" => Some(Operator::BitXor),
+            "~" => Some(Operator::BitNot),
+            "&=" => Some(Operator::BitAndAssign),
+            "|=" => Some(Operator::BitOrAssign),
+            "^=" => Some(Operator::BitXorAssign),
+            "&&" => Some(Operator::And),
+            "||" => Some(Operator::Or),
+            "??" => Some(Operator::Nullish),
+            "&&=" => Some(Operator::AndAssign),
+            "||=" => Some(Operator::OrAssign),
+            "??=" => Some(Operator::NullishAssign),
+            "?." => Some(Operator::OptionalChain),
+            "=>" => Some(Operator::Arrow),
+            "..." => Some(Operator::Spread),
             _ => None,
         }
     }
+
+    /// The operator as it is written in source
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operator::Assignment => "=",
+            Operator::Not => "!",
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Div => "/",
+            Operator::Mod => "%",
+            Operator::Mult => "*",
+            Operator::Eq => "==",
+            Operator::Ne => "!=",
+            Operator::Gt => ">",
+            Operator::Gte => ">=",
+            Operator::Lt => "<",
+            Operator::Lte => "<=",
+            Operator::Dot => ".",
+            Operator::AddAssign => "+=",
+            Operator::SubAssign => "-=",
+            Operator::MulAssign => "*=",
+            Operator::DivAssign => "/=",
+            Operator::ModAssign => "%=",
+            Operator::Inc => "++",
+            Operator::Dec => "--",
+            Operator::StrictEq => "===",
+            Operator::StrictNe => "!==",
+            Operator::Exp => "**",
+            Operator::ExpAssign => "**=",
+            Operator::Shl => "<<",
+            Operator::Shr => ">>",
+            Operator::UShr => ">>>",
+            Operator::ShlAssign => "<<=",
+            Operator::ShrAssign => ">>=",
+            Operator::UShrAssign => ">>>=",
+            Operator::BitAnd => "&",
+            Operator::BitOr => "|",
+            Operator::BitXor => "^",
+            Operator::BitNot => "~",
+            Operator::BitAndAssign => "&=",
+            Operator::BitOrAssign => "|=",
+            Operator::BitXorAssign => "^=",
+            Operator::And => "&&",
+            Operator::Or => "||",
+            Operator::Nullish => "??",
+            Operator::AndAssign => "&&=",
+            Operator::OrAssign => "||=",
+            Operator::NullishAssign => "??=",
+            Operator::OptionalChain => "?.",
+            Operator::Arrow => "=>",
+            Operator::Spread => "...",
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// All punctuation types
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Punctuation {
     Semicolon,
     Comma,
@@ -137,6 +732,8 @@ pub enum Punctuation {
     CloseSquiggle,
     OpenBracket,
     CloseBracket,
+    Question,
+    Colon,
 }
 
 impl Punctuation {
@@ -151,7 +748,142 @@ impl Punctuation {
             "}" => Some(Punctuation::CloseSquiggle),
             "[" => Some(Punctuation::OpenBracket),
             "]" => Some(Punctuation::CloseBracket),
+            "?" => Some(Punctuation::Question),
+            ":" => Some(Punctuation::Colon),
             _ => None,
         }
     }
+
+    /// The punctuation mark as it is written in source
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Punctuation::Semicolon => ";",
+            Punctuation::Comma => ",",
+            Punctuation::OpenParen => "(",
+            Punctuation::CloseParen => ")",
+            Punctuation::OpenSquiggle => "{",
+            Punctuation::CloseSquiggle => "}",
+            Punctuation::OpenBracket => "[",
+            Punctuation::CloseBracket => "]",
+            Punctuation::Question => "?",
+            Punctuation::Colon => ":",
+        }
+    }
+}
+
+impl fmt::Display for Punctuation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Serializes numbers so that ones JSON cannot hold, such as the infinity `1e400` lexes to, are
+/// written as strings rather than `null`
+#[cfg(feature = "serde")]
+mod number_serde {
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(num: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        match *num {
+            num if num.is_finite() => serializer.serialize_f64(num),
+            num if num.is_nan() => serializer.serialize_str("NaN"),
+            num if num > 0.0 => serializer.serialize_str("Infinity"),
+            _ => serializer.serialize_str("-Infinity"),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        deserializer.deserialize_any(NumberVisitor)
+    }
+
+    /// Reads either form [`serialize`] writes
+    struct NumberVisitor;
+
+    impl de::Visitor<'_> for NumberVisitor {
+        type Value = f64;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("a number, \"Infinity\", \"-Infinity\" or \"NaN\"")
+        }
+
+        fn visit_f64<E: de::Error>(self, num: f64) -> Result<f64, E> {
+            Ok(num)
+        }
+
+        fn visit_i64<E: de::Error>(self, num: i64) -> Result<f64, E> {
+            Ok(num as f64)
+        }
+
+        fn visit_u64<E: de::Error>(self, num: u64) -> Result<f64, E> {
+            Ok(num as f64)
+        }
+
+        fn visit_str<E: de::Error>(self, text: &str) -> Result<f64, E> {
+            match text {
+                "Infinity" => Ok(f64::INFINITY),
+                "-Infinity" => Ok(f64::NEG_INFINITY),
+                "NaN" => Ok(f64::NAN),
+                _ => Err(E::invalid_value(de::Unexpected::Str(text), &self)),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::{
+        lexer::Lexer,
+        symbol::{interner_with, Interner},
+    };
+
+    use super::{Keyword, Token, TokenType};
+
+    #[test]
+    fn tokens_round_trip_through_json() {
+        let input = r"let x = `a${1n}b` + 'c\'' /* note */ ?? #y * 1e400";
+        let mut lexer = Lexer::new(input);
+        let tokens: Vec<_> = lexer
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("Failed to lex input");
+
+        let json = serde_json::to_string(&tokens).expect("Failed to serialize tokens");
+        let read: Vec<Token<'_>> = serde_json::from_str(&json).expect("Failed to read tokens");
+        assert_eq!(read, tokens);
+        assert!(json.contains(r#"{"Number":"Infinity"}"#));
+
+        // Names read back the same along with the interner they were lexed with
+        let json = serde_json::to_string(lexer.interner()).expect("Failed to serialize interner");
+        assert_eq!(json, r#"["x","y"]"#);
+        let interner: Interner = serde_json::from_str(&json).expect("Failed to read interner");
+        let names: Vec<_> = read
+            .iter()
+            .filter_map(|token| token.token_type.identifier_name())
+            .map(|name| interner.resolve(name))
+            .collect();
+        assert_eq!(names, ["let", "x"]);
+    }
+
+    #[test]
+    fn token_types_serialize_readably() {
+        let json = |token_type: TokenType<'_>| serde_json::to_string(&token_type).unwrap();
+
+        assert_eq!(
+            json(TokenType::Keyword(Keyword::Let)),
+            r#"{"Keyword":"Let"}"#
+        );
+        // Names are written as their symbol, so the interner must be written alongside
+        let (_, [x]) = interner_with(["x"]);
+        assert_eq!(
+            json(TokenType::Identifier(x)),
+            format!(r#"{{"Identifier":{}}}"#, x.as_u32())
+        );
+        assert_eq!(
+            json(TokenType::String {
+                cooked: "a".into(),
+                raw: "'a'".into()
+            }),
+            r#"{"String":{"cooked":"a","raw":"'a'"}}"#
+        );
+    }
 }
@@ -1,6 +1,6 @@
 //! AST Implementation
 
-use scriptkiddie_lexer::token::Operator;
+use scriptkiddie_lexer::{symbol::Symbol, token::Operator};
 
 /// An ASTNode built from tokens
 #[derive(Debug, Clone, PartialEq)]
@@ -10,9 +10,9 @@ pub enum ASTNode {
     /// A function wrt it's name, params and body
     FunctionDeclaration {
         /// Function name
-        name: String,
+        name: Symbol,
         /// Function parameters
-        params: Vec<String>,
+        params: Vec<Symbol>,
         /// Function body
         body: Vec<ASTNode>,
     },
@@ -21,27 +21,133 @@ pub enum ASTNode {
         /// Whether the variable is let, const or var
         kind: VariableKind,
         /// Variable name
-        name: String,
+        name: Symbol,
         /// What defines the variable
         initializer: Option<Box<ASTNode>>,
     },
     /// An arbitrary expression
     ExpressionStatement(Box<ASTNode>),
-    /// A binary expression
+    /// A binary expression, including the logical `&&`, `||` and `??`
     BinaryExpression {
         /// The operator acting on two operands
-        operator: Operator,
+        operator: BinaryOperator,
         /// The left operand
         left: Box<ASTNode>,
         /// The right operand
         right: Box<ASTNode>,
     },
+    /// An operator acting on a single operand, such as `-a` or `typeof a`
+    UnaryExpression {
+        /// The operator
+        operator: UnaryOperator,
+        /// The operand
+        argument: Box<ASTNode>,
+    },
+    /// An increment or decrement, `++a`, `a++`, `--a` or `a--`
+    UpdateExpression {
+        /// Either `++` or `--`
+        operator: Operator,
+        /// Whether the operator comes before its operand
+        prefix: bool,
+        /// The variable being updated
+        argument: Box<ASTNode>,
+    },
+    /// An assignment, plain or compound such as `a += 1`
+    AssignmentExpression {
+        /// The assignment operator
+        operator: Operator,
+        /// What is assigned to
+        target: Box<ASTNode>,
+        /// The value assigned
+        value: Box<ASTNode>,
+    },
+    /// A conditional expression, `test ? consequent : alternate`
+    ConditionalExpression {
+        /// The condition
+        test: Box<ASTNode>,
+        /// The value if the condition is truthy
+        consequent: Box<ASTNode>,
+        /// The value otherwise
+        alternate: Box<ASTNode>,
+    },
+    /// Expressions separated by commas, evaluating to the last
+    SequenceExpression(Vec<ASTNode>),
     /// An identifier
-    Identifier(String),
+    Identifier(Symbol),
     /// A number
     NumberLiteral(f64),
+    /// An arbitrary precision integer, as its decimal digits
+    BigIntLiteral(String),
     /// A string
     StringLiteral(String),
+    /// `true` or `false`
+    BooleanLiteral(bool),
+    /// `null`
+    NullLiteral,
+    /// A JSX element, or a fragment when it has no name
+    #[cfg(feature = "jsx")]
+    JsxElement {
+        /// The tag name as written, such as `div`, `Foo.Bar` or `svg:rect`
+        name: Option<Symbol>,
+        /// The attributes of the opening tag
+        attributes: Vec<JsxAttribute>,
+        /// The text, elements and expressions between the tags
+        children: Vec<ASTNode>,
+    },
+    /// Text between JSX tags, with the whitespace around line breaks collapsed
+    #[cfg(feature = "jsx")]
+    JsxText(String),
+}
+
+/// An attribute of a JSX element
+#[cfg(feature = "jsx")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsxAttribute {
+    /// A named attribute, `name`, `name="value"` or `name={value}`
+    Named {
+        /// Attribute name
+        name: Symbol,
+        /// The value, or `None` for a bare attribute which is `true`
+        value: Option<ASTNode>,
+    },
+    /// Attributes spread from an object, `{...props}`
+    Spread(ASTNode),
+}
+
+/// An operator taking two operands
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryOperator {
+    /// An operator written as a symbol, such as `+` or `&&`
+    Operator(Operator),
+    /// `in`, testing whether an object has a property
+    In,
+    /// `instanceof`, testing an object's prototype chain
+    Instanceof,
+}
+
+impl From<Operator> for BinaryOperator {
+    fn from(operator: Operator) -> Self {
+        BinaryOperator::Operator(operator)
+    }
+}
+
+/// An operator taking a single operand
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnaryOperator {
+    /// `+`, converting to a number
+    Plus,
+    /// `-`, negating a number
+    Minus,
+    /// `!`, logical not
+    Not,
+    /// `~`, bitwise not
+    BitNot,
+    /// `typeof`
+    Typeof,
+    /// `void`, discarding the value
+    Void,
+    /// `delete`, removing a property
+    Delete,
 }
 
 /// The scopes a variable can have
@@ -1,12 +1,17 @@
 //! Parser struct definitions
 
-
-use scriptkiddie_lexer::token::{Keyword, Token, TokenType};
+use scriptkiddie_lexer::{
+    lexer::{goal::Goal, LexError},
+    symbol::Interner,
+    token::{Keyword, Token, TokenType},
+};
 
 use crate::ast::ASTNode;
 
 pub mod declaration;
 pub mod expression;
+#[cfg(feature = "jsx")]
+pub mod jsx;
 
 /// A result with error type Parsing Error
 pub type Result<T> = std::result::Result<T, AstParseError>;
@@ -19,48 +24,96 @@ pub enum AstParseError {
     UnexpectedEof,
     /// When a token is not expected
     #[error("Unexpected token {0:?}")]
-    UnexpectedToken(Token),
-    /// When a token pattern is not registered and therefore cannot be constructed into an ASTNode
-    #[error("Token pattern not recognized")]
-    UnknownTokenPattern,
+    UnexpectedToken(Token<'static>),
+    /// When the source could not be lexed into tokens in the first place
+    #[error(transparent)]
+    Lex(#[from] LexError),
 }
 
 /// A parser that holds onto a mutable context of a Lexer
 pub struct Parser<'lex> {
     /// The internal lexer session
-    tokens: &'lex [Token],
-    /// The current token we're pointing at
-    place: usize
+    tokens: &'lex [Token<'lex>],
+    /// The current token we're pointing at, which has not been consumed yet
+    place: usize,
+    /// The interner the tokens were lexed with, which names in the AST also resolve with
+    interner: &'lex mut Interner,
+    /// The goal the tokens were lexed with, which decides the words reserved as names
+    goal: Goal,
 }
 
 impl<'lex> Parser<'lex> {
-    /// Creates a new parser session from a token array
-    pub fn new(tokens: &'lex [Token]) -> Self {
-        Self { tokens, place: 0 }
+    /// Creates a new parser session from a token array and the interner it was lexed with
+    pub fn new(tokens: &'lex [Token<'lex>], interner: &'lex mut Interner) -> Self {
+        Self {
+            tokens,
+            place: 0,
+            interner,
+            goal: Goal::Script,
+        }
+    }
+
+    /// Sets the goal the tokens were lexed with. This should match the lexer's, and is a sloppy
+    /// mode [`Goal::Script`] unless told otherwise
+    pub fn with_goal(mut self, goal: Goal) -> Self {
+        self.goal = goal;
+        self
+    }
+
+    /// The interner names in the AST resolve with
+    pub fn interner(&self) -> &Interner {
+        self.interner
     }
 
     /// Gets the current token that's being pointed to
-    pub(crate) fn place(&self) -> Option<Token> {
+    pub(crate) fn place(&self) -> Option<Token<'lex>> {
         self.tokens.get(self.place).cloned()
     }
 
     /// Advances the pointer by 1
     pub(crate) fn advance(&mut self) {
-        self.place+=1
-    }
-
-    /// Advances the pointer by 1 and then gets the current token that's being pointed to
-    pub(crate) fn next(&mut self) -> Option<Token> {
-        self.advance();
-        self.place()
+        self.place += 1
     }
 
     /// Looks forward by n and then gets the current token that's being pointed to
-    pub(crate) fn peek(&self, n: usize) -> Option<Token> {
+    #[cfg(feature = "jsx")]
+    pub(crate) fn peek(&self, n: usize) -> Option<Token<'lex>> {
         let idx = self.place + n;
         self.tokens.get(idx).cloned()
     }
 
+    /// Whether the current token is of a certain type
+    pub(crate) fn at(&self, token_type: &TokenType<'_>) -> bool {
+        self.place()
+            .is_some_and(|token| token.token_type == *token_type)
+    }
+
+    /// Consumes the current token if it is of a certain type, returning whether it was
+    pub(crate) fn eat(&mut self, token_type: &TokenType<'_>) -> bool {
+        let at = self.at(token_type);
+        if at {
+            self.advance();
+        }
+        at
+    }
+
+    /// Consumes the current token, failing unless it is of a certain type
+    pub(crate) fn expect(&mut self, token_type: &TokenType<'_>) -> Result<()> {
+        if self.eat(token_type) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// An error for the current token not being what was expected
+    pub(crate) fn unexpected(&self) -> AstParseError {
+        match self.place() {
+            Some(token) => AstParseError::UnexpectedToken(token.into_owned()),
+            None => AstParseError::UnexpectedEof,
+        }
+    }
+
     /// Parses the tokens generated by the lexer and returns an AST
     pub fn parse_program(&mut self) -> Result<ASTNode> {
         let mut body = vec![];
@@ -78,7 +131,11 @@ impl<'lex> Parser<'lex> {
                 TokenType::Keyword(Keyword::Let)
                 | TokenType::Keyword(Keyword::Const)
                 | TokenType::Keyword(Keyword::Var) => self.parse_declaration(),
-                _ => self.parse_expression(),
+                _ => {
+                    let expression = self.parse_expression()?;
+                    self.consume_semicolon()?;
+                    Ok(ASTNode::ExpressionStatement(Box::new(expression)))
+                }
             },
             None => Err(AstParseError::UnexpectedEof),
         }
@@ -9,7 +9,7 @@ use super::{AstParseError, Parser, Result};
 impl<'lex> Parser<'lex> {
     /// Parses a variable declaration with a scope
     pub(crate) fn parse_declaration(&mut self) -> Result<ASTNode> {
-        let kind = match self.next() {
+        let kind = match self.place() {
             Some(Token {
                 token_type: TokenType::Keyword(Keyword::Let),
                 ..
@@ -22,31 +22,27 @@ impl<'lex> Parser<'lex> {
                 token_type: TokenType::Keyword(Keyword::Var),
                 ..
             }) => VariableKind::Var,
-            Some(token) => return Err(AstParseError::UnexpectedToken(token)),
+            Some(token) => return Err(AstParseError::UnexpectedToken(token.into_owned())),
             None => return Err(AstParseError::UnexpectedEof),
         };
+        self.advance();
 
-        let name = match self.next() {
-            Some(Token {
-                token_type: TokenType::Identifier(name),
-                ..
-            }) => name,
-            Some(token) => return Err(AstParseError::UnexpectedToken(token)),
+        let name = match self.place() {
+            Some(token) => match token.token_type.as_identifier(self.goal) {
+                Some(name) => name,
+                None => return Err(AstParseError::UnexpectedToken(token.into_owned())),
+            },
             None => return Err(AstParseError::UnexpectedEof),
         };
+        self.advance();
 
-        let initializer = if let Some(Token {
-            token_type: TokenType::Operator(Operator::Assignment),
-            ..
-        }) = self.peek(1)
-        {
-            self.next();
-            Some(Box::new(self.parse_expression()?))
+        let initializer = if self.eat(&TokenType::Operator(Operator::Assignment)) {
+            Some(Box::new(self.parse_assignment()?))
         } else {
             None
         };
 
-        self.consume_punctuation(Punctuation::Semicolon)?;
+        self.consume_semicolon()?;
 
         Ok(ASTNode::VariableDeclaration {
             kind,
@@ -55,43 +51,99 @@ impl<'lex> Parser<'lex> {
         })
     }
 
-    /// Consumes the ending punctuation of a line
-    pub(crate) fn consume_punctuation(&mut self, expected: Punctuation) -> Result<()> {
-        match self.next() {
+    /// Consumes the `;` ending a statement, or inserts one automatically when the next token is
+    /// on a new line, is a `}` or the input has ended
+    pub(crate) fn consume_semicolon(&mut self) -> Result<()> {
+        match self.place() {
+            Some(Token {
+                token_type: TokenType::Punctuation(Punctuation::Semicolon),
+                ..
+            }) => {
+                self.advance();
+                Ok(())
+            }
             Some(Token {
-                token_type: TokenType::Punctuation(punc),
+                token_type: TokenType::Punctuation(Punctuation::CloseSquiggle),
                 ..
-            }) if punc == expected => Ok(()),
-            Some(token) => Err(AstParseError::UnexpectedToken(token)),
-            None => Err(AstParseError::UnexpectedEof),
+            })
+            | None => Ok(()),
+            Some(token) if token.newline_before => Ok(()),
+            Some(token) => Err(AstParseError::UnexpectedToken(token.into_owned())),
         }
     }
+
+    /// Consumes a punctuation mark, failing if the current token is anything else
+    pub(crate) fn consume_punctuation(&mut self, expected: Punctuation) -> Result<()> {
+        self.expect(&TokenType::Punctuation(expected))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use scriptkiddie_lexer::lexer::Lexer;
+    use scriptkiddie_lexer::{
+        lexer::{goal::Goal, Lexer},
+        symbol::Interner,
+    };
 
     use crate::{
         ast::{ASTNode, VariableKind},
-        parser::Parser,
+        parser::{AstParseError, Parser},
     };
 
     #[test]
     fn parse_declarations() {
         let input = "let a;".to_string();
-        let lexer = Lexer::new(input);
-        let tokens: Vec<_> = lexer.collect();
-        let mut parser = Parser::new(&tokens);
+        let mut interner = Interner::new();
+        let a = interner.intern("a");
+        let mut lexer = Lexer::new(&input).with_interner(interner);
+        let tokens: Vec<_> = lexer
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("Failed to lex input");
+        let mut interner = lexer.into_interner();
+        let mut parser = Parser::new(&tokens, &mut interner);
 
         let parsed = parser.parse_program().expect("Failed to parse expression");
         assert_eq!(
             ASTNode::Program(vec![ASTNode::VariableDeclaration {
                 kind: VariableKind::Let,
-                name: "a".into(),
+                name: a,
                 initializer: None
             }]),
             parsed
         )
     }
+
+    #[test]
+    fn declarations_reject_names_reserved_by_the_goal() {
+        let cases = [
+            ("let await = 1;", Goal::Script, true),
+            ("let await = 1;", Goal::Module, false),
+            ("var yield;", Goal::Script, true),
+            ("var yield;", Goal::StrictScript, false),
+            ("var let;", Goal::StrictScript, false),
+        ];
+
+        for (input, goal, allowed) in cases {
+            let mut lexer = Lexer::new(input).with_goal(goal);
+            let tokens: Vec<_> = lexer
+                .by_ref()
+                .collect::<Result<_, _>>()
+                .expect("Failed to lex input");
+            let mut interner = lexer.into_interner();
+            let parsed = Parser::new(&tokens, &mut interner)
+                .with_goal(goal)
+                .parse_program();
+
+            if allowed {
+                parsed.unwrap_or_else(|err| panic!("Failed on {input:?} in a {goal:?}: {err}"));
+            } else {
+                let err = parsed.expect_err(input);
+                assert!(
+                    matches!(err, AstParseError::UnexpectedToken(_)),
+                    "{input:?}"
+                );
+            }
+        }
+    }
 }
@@ -1,17 +1,502 @@
-//! Parser rules for expression statements
+//! Parser rules for expressions. Binary operators are read by precedence climbing, while every
+//! looser level of the grammar (comma, assignment and conditional) and every tighter one (unary,
+//! update and primary expressions) has a rule of its own
 
-use crate::{ast::ASTNode, syntax::SYNTAX_PATTERNS};
+use scriptkiddie_lexer::token::{Keyword, Operator, Punctuation, TokenType};
+
+use crate::ast::{ASTNode, BinaryOperator, UnaryOperator};
 
 use super::{AstParseError, Parser, Result};
 
+/// The precedence of `&&`. Operands of `??` must bind tighter than this, as mixing it with `&&`
+/// or `||` requires parentheses
+const AND_PRECEDENCE: u8 = 2;
+
+/// The binary operator a token is, along with its precedence. Operators with a higher precedence
+/// bind tighter
+fn binary_operator(token_type: &TokenType<'_>) -> Option<(BinaryOperator, u8)> {
+    let (operator, precedence) = match token_type {
+        TokenType::Keyword(Keyword::In) => (BinaryOperator::In, 7),
+        TokenType::Keyword(Keyword::Instanceof) => (BinaryOperator::Instanceof, 7),
+        TokenType::Operator(op) => {
+            let precedence = match op {
+                Operator::Or | Operator::Nullish => 1,
+                Operator::And => AND_PRECEDENCE,
+                Operator::BitOr => 3,
+                Operator::BitXor => 4,
+                Operator::BitAnd => 5,
+                Operator::Eq | Operator::Ne | Operator::StrictEq | Operator::StrictNe => 6,
+                Operator::Lt | Operator::Gt | Operator::Lte | Operator::Gte => 7,
+                Operator::Shl | Operator::Shr | Operator::UShr => 8,
+                Operator::Add | Operator::Sub => 9,
+                Operator::Mult | Operator::Div | Operator::Mod => 10,
+                Operator::Exp => 11,
+                _ => return None,
+            };
+            (op.clone().into(), precedence)
+        }
+        _ => return None,
+    };
+
+    Some((operator, precedence))
+}
+
+/// The unary operator a token is, not including `++` and `--`
+fn unary_operator(token_type: &TokenType<'_>) -> Option<UnaryOperator> {
+    match token_type {
+        TokenType::Operator(Operator::Add) => Some(UnaryOperator::Plus),
+        TokenType::Operator(Operator::Sub) => Some(UnaryOperator::Minus),
+        TokenType::Operator(Operator::Not) => Some(UnaryOperator::Not),
+        TokenType::Operator(Operator::BitNot) => Some(UnaryOperator::BitNot),
+        TokenType::Keyword(Keyword::Typeof) => Some(UnaryOperator::Typeof),
+        TokenType::Keyword(Keyword::Void) => Some(UnaryOperator::Void),
+        TokenType::Keyword(Keyword::Delete) => Some(UnaryOperator::Delete),
+        _ => None,
+    }
+}
+
+/// Whether an operator assigns to its left operand, such as `=` or `+=`
+fn is_assignment_operator(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::Assignment
+            | Operator::AddAssign
+            | Operator::SubAssign
+            | Operator::MulAssign
+            | Operator::DivAssign
+            | Operator::ModAssign
+            | Operator::ExpAssign
+            | Operator::ShlAssign
+            | Operator::ShrAssign
+            | Operator::UShrAssign
+            | Operator::BitAndAssign
+            | Operator::BitOrAssign
+            | Operator::BitXorAssign
+            | Operator::AndAssign
+            | Operator::OrAssign
+            | Operator::NullishAssign
+    )
+}
+
+/// Whether an expression can be assigned to or updated. Only plain names can be until member
+/// expressions are parsed
+fn is_simple_target(node: &ASTNode) -> bool {
+    matches!(node, ASTNode::Identifier(_))
+}
+
 impl<'lex> Parser<'lex> {
-    /// Parses an expression as an AST Node
+    /// Parses an expression as an AST Node, including a sequence of them separated by `,`
     pub(crate) fn parse_expression(&mut self) -> Result<ASTNode> {
-        for expression in SYNTAX_PATTERNS.iter() {
-            if expression.matches_pattern(self.tokens, self.place) {
-                return expression.parse_grammar(self.tokens, &mut self.place);
+        let first = self.parse_assignment()?;
+        if !self.at(&TokenType::Punctuation(Punctuation::Comma)) {
+            return Ok(first);
+        }
+
+        let mut expressions = vec![first];
+        while self.eat(&TokenType::Punctuation(Punctuation::Comma)) {
+            expressions.push(self.parse_assignment()?);
+        }
+
+        Ok(ASTNode::SequenceExpression(expressions))
+    }
+
+    /// Parses an assignment, or any expression binding tighter. Assignment is right associative,
+    /// so `a = b = c` assigns `c` to `b` first
+    pub(crate) fn parse_assignment(&mut self) -> Result<ASTNode> {
+        let target = self.parse_conditional()?;
+        let operator = match self.place().map(|token| token.token_type) {
+            Some(TokenType::Operator(op)) if is_assignment_operator(&op) => op,
+            _ => return Ok(target),
+        };
+        if !is_simple_target(&target) {
+            return Err(self.unexpected());
+        }
+        self.advance();
+
+        let value = self.parse_assignment()?;
+        Ok(ASTNode::AssignmentExpression {
+            operator,
+            target: Box::new(target),
+            value: Box::new(value),
+        })
+    }
+
+    /// Parses a conditional expression, or any expression binding tighter
+    fn parse_conditional(&mut self) -> Result<ASTNode> {
+        let test = self.parse_binary(0)?;
+        if !self.eat(&TokenType::Punctuation(Punctuation::Question)) {
+            return Ok(test);
+        }
+
+        let consequent = self.parse_assignment()?;
+        self.consume_punctuation(Punctuation::Colon)?;
+        let alternate = self.parse_assignment()?;
+
+        Ok(ASTNode::ConditionalExpression {
+            test: Box::new(test),
+            consequent: Box::new(consequent),
+            alternate: Box::new(alternate),
+        })
+    }
+
+    /// Parses an expression made of binary operators with a precedence above `min_precedence`.
+    /// Operators are left associative apart from `**`
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<ASTNode> {
+        // `-a ** b` is ambiguous, so a unary operand of `**` must be parenthesized
+        let mut unary_left = self
+            .place()
+            .is_some_and(|token| unary_operator(&token.token_type).is_some());
+        let mut left = self.parse_unary()?;
+        let mut combined_nullish = false;
+        let mut combined_and_or = false;
+
+        while let Some(token) = self.place() {
+            let Some((operator, precedence)) = binary_operator(&token.token_type) else {
+                break;
+            };
+            if precedence <= min_precedence {
+                break;
             }
+
+            let right_precedence = match operator {
+                BinaryOperator::Operator(Operator::Exp) if unary_left => {
+                    return Err(self.unexpected())
+                }
+                BinaryOperator::Operator(Operator::Exp) => precedence - 1,
+                BinaryOperator::Operator(Operator::Nullish) if combined_and_or => {
+                    return Err(self.unexpected())
+                }
+                BinaryOperator::Operator(Operator::Nullish) => {
+                    combined_nullish = true;
+                    AND_PRECEDENCE
+                }
+                BinaryOperator::Operator(Operator::And | Operator::Or) if combined_nullish => {
+                    return Err(self.unexpected())
+                }
+                BinaryOperator::Operator(Operator::And | Operator::Or) => {
+                    combined_and_or = true;
+                    precedence
+                }
+                _ => precedence,
+            };
+            self.advance();
+
+            let right = self.parse_binary(right_precedence)?;
+            left = ASTNode::BinaryExpression {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+            unary_left = false;
+        }
+
+        Ok(left)
+    }
+
+    /// Parses a unary operator or prefix `++` or `--` along with its operand, or any expression
+    /// binding tighter
+    fn parse_unary(&mut self) -> Result<ASTNode> {
+        let token = self.place().ok_or(AstParseError::UnexpectedEof)?;
+        if let Some(operator) = unary_operator(&token.token_type) {
+            self.advance();
+            let argument = self.parse_unary()?;
+            return Ok(ASTNode::UnaryExpression {
+                operator,
+                argument: Box::new(argument),
+            });
+        }
+
+        let operator = match &token.token_type {
+            TokenType::Operator(op @ (Operator::Inc | Operator::Dec)) => op.clone(),
+            _ => return self.parse_postfix(),
+        };
+        self.advance();
+
+        let argument = self.parse_unary()?;
+        if !is_simple_target(&argument) {
+            return Err(AstParseError::UnexpectedToken(token.into_owned()));
+        }
+
+        Ok(ASTNode::UpdateExpression {
+            operator,
+            prefix: true,
+            argument: Box::new(argument),
+        })
+    }
+
+    /// Parses a primary expression followed by a postfix `++` or `--`, which must be on the same
+    /// line or the operator begins the next statement instead
+    fn parse_postfix(&mut self) -> Result<ASTNode> {
+        let argument = self.parse_primary()?;
+        let operator = match self.place() {
+            Some(token) if !token.newline_before => match token.token_type {
+                TokenType::Operator(op @ (Operator::Inc | Operator::Dec)) => op,
+                _ => return Ok(argument),
+            },
+            _ => return Ok(argument),
+        };
+        if !is_simple_target(&argument) {
+            return Err(self.unexpected());
         }
-        Err(AstParseError::UnknownTokenPattern)
+        self.advance();
+
+        Ok(ASTNode::UpdateExpression {
+            operator,
+            prefix: false,
+            argument: Box::new(argument),
+        })
+    }
+
+    /// Parses a literal, a name or an expression in parentheses
+    fn parse_primary(&mut self) -> Result<ASTNode> {
+        #[cfg(feature = "jsx")]
+        if self.at_jsx_element() {
+            return self.parse_jsx_element();
+        }
+
+        let token = self.place().ok_or(AstParseError::UnexpectedEof)?;
+        let node = match &token.token_type {
+            TokenType::Number(num) => ASTNode::NumberLiteral(*num),
+            TokenType::BigInt(digits) => ASTNode::BigIntLiteral(digits.to_string()),
+            TokenType::String { cooked, .. } => ASTNode::StringLiteral(cooked.to_string()),
+            TokenType::Keyword(Keyword::True) => ASTNode::BooleanLiteral(true),
+            TokenType::Keyword(Keyword::False) => ASTNode::BooleanLiteral(false),
+            TokenType::Keyword(Keyword::Null) => ASTNode::NullLiteral,
+            TokenType::Punctuation(Punctuation::OpenParen) => {
+                self.advance();
+                let expression = self.parse_expression()?;
+                self.consume_punctuation(Punctuation::CloseParen)?;
+                return Ok(expression);
+            }
+            token_type => match token_type.as_identifier(self.goal) {
+                Some(name) => ASTNode::Identifier(name),
+                None => return Err(self.unexpected()),
+            },
+        };
+        self.advance();
+
+        Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use scriptkiddie_lexer::{lexer::Lexer, symbol::Interner, token::Token};
+
+    use crate::{
+        ast::{ASTNode, BinaryOperator, UnaryOperator, VariableKind},
+        parser::{AstParseError, Parser},
+    };
+
+    /// Writes an expression as an S-expression, so its structure can be compared at a glance
+    fn sexp(node: &ASTNode, interner: &Interner) -> String {
+        match node {
+            ASTNode::Identifier(name) => interner.resolve(*name).to_string(),
+            ASTNode::NumberLiteral(num) => num.to_string(),
+            ASTNode::StringLiteral(string) => format!("{string:?}"),
+            ASTNode::BooleanLiteral(value) => value.to_string(),
+            ASTNode::NullLiteral => "null".to_string(),
+            ASTNode::BinaryExpression {
+                operator,
+                left,
+                right,
+            } => {
+                let operator = match operator {
+                    BinaryOperator::Operator(op) => op.as_str(),
+                    BinaryOperator::In => "in",
+                    BinaryOperator::Instanceof => "instanceof",
+                };
+                format!(
+                    "({operator} {} {})",
+                    sexp(left, interner),
+                    sexp(right, interner)
+                )
+            }
+            ASTNode::UnaryExpression { operator, argument } => {
+                let operator = match operator {
+                    UnaryOperator::Plus => "+",
+                    UnaryOperator::Minus => "-",
+                    UnaryOperator::Not => "!",
+                    UnaryOperator::BitNot => "~",
+                    UnaryOperator::Typeof => "typeof",
+                    UnaryOperator::Void => "void",
+                    UnaryOperator::Delete => "delete",
+                };
+                format!("({operator} {})", sexp(argument, interner))
+            }
+            ASTNode::UpdateExpression {
+                operator,
+                prefix: true,
+                argument,
+            } => format!("({} {})", operator.as_str(), sexp(argument, interner)),
+            ASTNode::UpdateExpression {
+                operator, argument, ..
+            } => format!("({} {})", sexp(argument, interner), operator.as_str()),
+            ASTNode::AssignmentExpression {
+                operator,
+                target,
+                value,
+            } => format!(
+                "({} {} {})",
+                operator.as_str(),
+                sexp(target, interner),
+                sexp(value, interner)
+            ),
+            ASTNode::ConditionalExpression {
+                test,
+                consequent,
+                alternate,
+            } => format!(
+                "(? {} {} {})",
+                sexp(test, interner),
+                sexp(consequent, interner),
+                sexp(alternate, interner)
+            ),
+            ASTNode::SequenceExpression(expressions) => {
+                let expressions: Vec<_> = expressions
+                    .iter()
+                    .map(|expression| sexp(expression, interner))
+                    .collect();
+                format!("(, {})", expressions.join(" "))
+            }
+            node => panic!("Unexpected node {node:?}"),
+        }
+    }
+
+    /// Lexes some input, panicking on any error, along with the interner its names resolve with
+    fn lex(input: &str, interner: Interner) -> (Vec<Token<'_>>, Interner) {
+        let mut lexer = Lexer::new(input).with_interner(interner);
+        let tokens = lexer
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("Failed to lex input");
+        (tokens, lexer.into_interner())
+    }
+
+    /// Parses a whole input as a single expression, written as an S-expression
+    fn parse(input: &str) -> Result<String, AstParseError> {
+        let (tokens, mut interner) = lex(input, Interner::new());
+        let mut parser = Parser::new(&tokens, &mut interner);
+
+        let expression = parser.parse_expression()?;
+        match parser.place() {
+            Some(_) => Err(parser.unexpected()),
+            None => Ok(sexp(&expression, parser.interner())),
+        }
+    }
+
+    /// Checks that every input parses to its S-expression
+    fn assert_parses(cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            let parsed = parse(input).unwrap_or_else(|err| panic!("Failed on {input:?}: {err}"));
+            assert_eq!(parsed, *expected, "Parsing {input:?}");
+        }
+    }
+
+    #[test]
+    fn binary_operators_follow_precedence_and_associativity() {
+        assert_parses(&[
+            ("a + b * c", "(+ a (* b c))"),
+            ("a - b - c", "(- (- a b) c)"),
+            ("a ** b ** c", "(** a (** b c))"),
+            ("(a + b) * c % d", "(% (* (+ a b) c) d)"),
+            ("a < b == c instanceof d", "(== (< a b) (instanceof c d))"),
+            ("a | b ^ c & d << e", "(| a (^ b (& c (<< d e))))"),
+            ("a in b >>> 1 - 2", "(in a (>>> b (- 1 2)))"),
+            ("a !== b === 'c'", "(=== (!== a b) \"c\")"),
+        ]);
+    }
+
+    #[test]
+    fn logical_conditional_assignment_and_comma_expressions() {
+        assert_parses(&[
+            ("a || b && c", "(|| a (&& b c))"),
+            ("a ?? b | c", "(?? a (| b c))"),
+            ("a ?? b ?? c", "(?? (?? a b) c)"),
+            ("(a || b) ?? c", "(?? (|| a b) c)"),
+            ("a ? b : c ? d : e", "(? a b (? c d e))"),
+            ("a ? b = 1 : c", "(? a (= b 1) c)"),
+            ("a = b += c ||= d", "(= a (+= b (||= c d)))"),
+            ("a = b ? c : d, e", "(, (= a (? b c d)) e)"),
+        ]);
+
+        for input in [
+            "a ?? b || c",
+            "a && b ?? c",
+            "a || b ?? c",
+            "a + b = c",
+            "(a ? b : c) = d",
+            "a ? b",
+        ] {
+            assert!(parse(input).is_err(), "{input:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn unary_and_update_expressions() {
+        assert_parses(&[
+            ("!typeof void a", "(! (typeof (void a)))"),
+            ("- -a", "(- (- a))"),
+            ("(-a) ** 2", "(** (- a) 2)"),
+            ("2 ** -a", "(** 2 (- a))"),
+            ("++a ** 2", "(** (++ a) 2)"),
+            ("++a * b--", "(* (++ a) (b --))"),
+            ("a+++b", "(+ (a ++) b)"),
+            ("delete a, true, null", "(, (delete a) true null)"),
+        ]);
+
+        for input in ["-a ** 2", "a * ~b ** c", "++1", "(a + b)++", "++a++"] {
+            assert!(parse(input).is_err(), "{input:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn parse_programs_with_expressions() {
+        let mut input = String::new();
+        std::fs::File::open("../test/simple.js")
+            .and_then(|mut file| file.read_to_string(&mut input))
+            .expect("Failed to read file");
+        let mut interner = Interner::new();
+        let [a, b, c] = ["a", "b", "c"].map(|name| interner.intern(name));
+        let (tokens, mut interner) = lex(&input, interner);
+
+        let ASTNode::Program(body) = Parser::new(&tokens, &mut interner)
+            .parse_program()
+            .expect("Failed to parse program")
+        else {
+            panic!("Expected a program");
+        };
+        assert_eq!(body.len(), 3);
+        assert_eq!(
+            body[2],
+            ASTNode::VariableDeclaration {
+                kind: VariableKind::Let,
+                name: c,
+                initializer: Some(Box::new(ASTNode::BinaryExpression {
+                    operator: scriptkiddie_lexer::token::Operator::Add.into(),
+                    left: Box::new(ASTNode::Identifier(a)),
+                    right: Box::new(ASTNode::Identifier(b)),
+                })),
+            }
+        );
+
+        // A `++` on a new line begins the next statement
+        let (tokens, mut interner) = lex("a\n++b", interner);
+        let program = Parser::new(&tokens, &mut interner)
+            .parse_program()
+            .expect("Failed to parse program");
+        let ASTNode::Program(body) = program else {
+            panic!("Expected a program");
+        };
+        let statements: Vec<_> = body
+            .iter()
+            .map(|statement| match statement {
+                ASTNode::ExpressionStatement(expression) => sexp(expression, &interner),
+                statement => panic!("Unexpected statement {statement:?}"),
+            })
+            .collect();
+        assert_eq!(statements, vec!["a", "(++ b)"]);
     }
 }
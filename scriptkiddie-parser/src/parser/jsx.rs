@@ -0,0 +1,405 @@
+//! Parser rules for JSX elements, only built with the `jsx` feature
+
+use scriptkiddie_lexer::{
+    symbol::Symbol,
+    token::{Operator, Punctuation, TokenType},
+};
+
+use crate::ast::{ASTNode, JsxAttribute};
+
+use super::{AstParseError, Parser, Result};
+
+mod entities;
+
+use entities::{ENTITIES, MAX_ENTITY_LEN};
+
+/// Decodes a character reference without its `&` and `;`, such as `amp`, `#123` or `#x7B`, onto
+/// the end of `decoded`. Returns whether it was a reference at all
+fn decode_entity(name: &str, decoded: &mut String) -> bool {
+    let Some(number) = name.strip_prefix('#') else {
+        let Ok(index) = ENTITIES.binary_search_by_key(&name, |&(name, _)| name) else {
+            return false;
+        };
+        decoded.push_str(ENTITIES[index].1);
+        return true;
+    };
+
+    let (digits, radix) = match number.strip_prefix(['x', 'X']) {
+        Some(hex) => (hex, 16),
+        None => (number, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return false;
+    }
+
+    match u32::from_str_radix(digits, radix)
+        .ok()
+        .and_then(char::from_u32)
+    {
+        Some(c) => {
+            decoded.push(c);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Decodes the HTML character references in JSX text or an attribute string. Anything that is
+/// not a known reference ending in `;` is kept as written
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp + 1..];
+
+        let end = rest
+            .char_indices()
+            .take(MAX_ENTITY_LEN + 1)
+            .find(|&(_, c)| c == ';')
+            .map(|(end, _)| end);
+        match end {
+            Some(end) if decode_entity(&rest[..end], &mut decoded) => rest = &rest[end + 1..],
+            _ => decoded.push('&'),
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Collapses the whitespace of JSX text the way JSX does: spaces and tabs are trimmed where lines
+/// meet a line break, blank lines are dropped and the rest are joined with single spaces. Other
+/// whitespace such as a non-breaking space is kept, as is anything from a character reference
+fn clean_jsx_text(text: &str) -> String {
+    const TRIMMED: [char; 2] = [' ', '\t'];
+
+    let lines: Vec<_> = text.split(['\n', '\r', '\u{2028}', '\u{2029}']).collect();
+    let last = lines.len().saturating_sub(1);
+
+    let joined = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = if i > 0 {
+                line.trim_start_matches(TRIMMED)
+            } else {
+                line
+            };
+            if i < last {
+                line.trim_end_matches(TRIMMED)
+            } else {
+                line
+            }
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    decode_entities(&joined)
+}
+
+impl<'lex> Parser<'lex> {
+    /// Whether the current token begins a JSX element
+    pub(crate) fn at_jsx_element(&self) -> bool {
+        self.at(&TokenType::Operator(Operator::Lt))
+    }
+
+    /// Parses a JSX element or fragment along with all of its children
+    pub(crate) fn parse_jsx_element(&mut self) -> Result<ASTNode> {
+        self.expect(&TokenType::Operator(Operator::Lt))?;
+        let name = self.parse_jsx_name()?;
+
+        let mut attributes = vec![];
+        while !self.at(&TokenType::Operator(Operator::Gt))
+            && !self.at(&TokenType::Operator(Operator::Div))
+        {
+            attributes.push(self.parse_jsx_attribute()?);
+        }
+
+        if self.eat(&TokenType::Operator(Operator::Div)) {
+            self.expect(&TokenType::Operator(Operator::Gt))?;
+            return Ok(ASTNode::JsxElement {
+                name,
+                attributes,
+                children: vec![],
+            });
+        }
+        self.expect(&TokenType::Operator(Operator::Gt))?;
+
+        let mut children = vec![];
+        loop {
+            let token = self.place().ok_or(AstParseError::UnexpectedEof)?;
+            match &token.token_type {
+                TokenType::JsxText(text) => {
+                    self.advance();
+                    let text = clean_jsx_text(text);
+                    if !text.is_empty() {
+                        children.push(ASTNode::JsxText(text));
+                    }
+                }
+                TokenType::Punctuation(Punctuation::OpenSquiggle) => {
+                    self.advance();
+                    // An empty container, usually holding just a comment, is no child at all
+                    if !self.eat(&TokenType::Punctuation(Punctuation::CloseSquiggle)) {
+                        children.push(self.parse_assignment()?);
+                        self.expect(&TokenType::Punctuation(Punctuation::CloseSquiggle))?;
+                    }
+                }
+                TokenType::Operator(Operator::Lt)
+                    if self.peek(1).is_some_and(|next| {
+                        next.token_type == TokenType::Operator(Operator::Div)
+                    }) =>
+                {
+                    self.advance();
+                    self.advance();
+                    break;
+                }
+                TokenType::Operator(Operator::Lt) => children.push(self.parse_jsx_element()?),
+                _ => return Err(AstParseError::UnexpectedToken(token.into_owned())),
+            }
+        }
+
+        // The closing tag must name the same element
+        let closing = self.place();
+        if self.parse_jsx_name()? != name {
+            let token = closing.ok_or(AstParseError::UnexpectedEof)?;
+            return Err(AstParseError::UnexpectedToken(token.into_owned()));
+        }
+        self.expect(&TokenType::Operator(Operator::Gt))?;
+
+        Ok(ASTNode::JsxElement {
+            name,
+            attributes,
+            children,
+        })
+    }
+
+    /// Parses a tag name including any `.` members or `:` namespace, or `None` for a fragment
+    fn parse_jsx_name(&mut self) -> Result<Option<Symbol>> {
+        let Some(first) = self.jsx_identifier() else {
+            return Ok(None);
+        };
+
+        let mut name = self.interner.resolve(first).to_string();
+        loop {
+            if self.eat(&TokenType::Operator(Operator::Dot)) {
+                name.push('.');
+            } else if self.eat(&TokenType::Punctuation(Punctuation::Colon)) {
+                name.push(':');
+            } else {
+                break;
+            }
+
+            let part = self.jsx_identifier().ok_or_else(|| self.unexpected())?;
+            name.push_str(self.interner.resolve(part));
+        }
+
+        Ok(Some(self.interner.intern(&name)))
+    }
+
+    /// Parses a single attribute of an opening tag
+    fn parse_jsx_attribute(&mut self) -> Result<JsxAttribute> {
+        if self.eat(&TokenType::Punctuation(Punctuation::OpenSquiggle)) {
+            self.expect(&TokenType::Operator(Operator::Spread))?;
+            let spread = self.parse_assignment()?;
+            self.expect(&TokenType::Punctuation(Punctuation::CloseSquiggle))?;
+            return Ok(JsxAttribute::Spread(spread));
+        }
+
+        let name = self.parse_jsx_name()?.ok_or_else(|| self.unexpected())?;
+        if !self.eat(&TokenType::Operator(Operator::Assignment)) {
+            return Ok(JsxAttribute::Named { name, value: None });
+        }
+
+        let token = self.place().ok_or(AstParseError::UnexpectedEof)?;
+        let value = match token.token_type {
+            TokenType::String { cooked, .. } => {
+                self.advance();
+                ASTNode::StringLiteral(decode_entities(&cooked))
+            }
+            TokenType::Punctuation(Punctuation::OpenSquiggle) => {
+                self.advance();
+                let value = self.parse_assignment()?;
+                self.expect(&TokenType::Punctuation(Punctuation::CloseSquiggle))?;
+                value
+            }
+            TokenType::Operator(Operator::Lt) => self.parse_jsx_element()?,
+            _ => return Err(AstParseError::UnexpectedToken(token.into_owned())),
+        };
+
+        Ok(JsxAttribute::Named {
+            name,
+            value: Some(value),
+        })
+    }
+
+    /// Consumes a JSX identifier if it is the current token
+    fn jsx_identifier(&mut self) -> Option<Symbol> {
+        match self.place()?.token_type {
+            TokenType::JsxIdentifier(name) => {
+                self.advance();
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scriptkiddie_lexer::{lexer::Lexer, symbol::Interner};
+
+    use crate::{
+        ast::{ASTNode, JsxAttribute},
+        parser::{AstParseError, Parser},
+    };
+
+    use super::{clean_jsx_text, decode_entities};
+
+    /// Parses a single JSX element with names interned into `interner`
+    fn parse(input: &str, interner: Interner) -> crate::parser::Result<ASTNode> {
+        let mut lexer = Lexer::new(input).with_interner(interner);
+        let tokens: Vec<_> = lexer
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("Failed to lex input");
+        let mut interner = lexer.into_interner();
+        Parser::new(&tokens, &mut interner).parse_jsx_element()
+    }
+
+    #[test]
+    fn parse_jsx_elements() {
+        let mut interner = Interner::new();
+        let [list, id, hidden, b, foo_bar] =
+            ["ui:List", "id", "hidden", "b", "Foo.Bar"].map(|name| interner.intern(name));
+        let parsed = parse(
+            r#"<ui:List id="main" hidden>
+                Hello <b>world</b>
+                <Foo.Bar />{}<></>
+            </ui:List>"#,
+            interner,
+        )
+        .expect("Failed to parse element");
+
+        assert_eq!(
+            parsed,
+            ASTNode::JsxElement {
+                name: Some(list),
+                attributes: vec![
+                    JsxAttribute::Named {
+                        name: id,
+                        value: Some(ASTNode::StringLiteral("main".into())),
+                    },
+                    JsxAttribute::Named {
+                        name: hidden,
+                        value: None,
+                    },
+                ],
+                children: vec![
+                    ASTNode::JsxText("Hello ".into()),
+                    ASTNode::JsxElement {
+                        name: Some(b),
+                        attributes: vec![],
+                        children: vec![ASTNode::JsxText("world".into())],
+                    },
+                    ASTNode::JsxElement {
+                        name: Some(foo_bar),
+                        attributes: vec![],
+                        children: vec![],
+                    },
+                    ASTNode::JsxElement {
+                        name: None,
+                        attributes: vec![],
+                        children: vec![],
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_jsx_expression_containers() {
+        let mut interner = Interner::new();
+        let [a, b, x, props, y] = ["a", "b", "x", "props", "y"].map(|name| interner.intern(name));
+        let parsed =
+            parse("<a b={x} {...props}>{y}{/* none */}</a>", interner).expect("Failed to parse");
+
+        assert_eq!(
+            parsed,
+            ASTNode::JsxElement {
+                name: Some(a),
+                attributes: vec![
+                    JsxAttribute::Named {
+                        name: b,
+                        value: Some(ASTNode::Identifier(x)),
+                    },
+                    JsxAttribute::Spread(ASTNode::Identifier(props)),
+                ],
+                children: vec![ASTNode::Identifier(y)],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_jsx_decodes_character_references() {
+        let mut interner = Interner::new();
+        let [a, title] = ["a", "title"].map(|name| interner.intern(name));
+        let parsed = parse(
+            r#"<a title="&quot;hi&quot; &amp c">&lt;b&gt; &#123;&#x7D;&nbsp;
+                &bogus; & x</a>"#,
+            interner,
+        )
+        .expect("Failed to parse");
+
+        assert_eq!(
+            parsed,
+            ASTNode::JsxElement {
+                name: Some(a),
+                attributes: vec![JsxAttribute::Named {
+                    name: title,
+                    value: Some(ASTNode::StringLiteral("\"hi\" &amp c".into())),
+                }],
+                children: vec![ASTNode::JsxText("<b> {}\u{A0} &bogus; & x".into())],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_jsx_rejects_mismatched_closing_tags() {
+        let err = parse("<a><b></a></b>", Interner::new()).expect_err("Tags should not match");
+        assert!(matches!(err, AstParseError::UnexpectedToken(_)));
+    }
+
+    #[test]
+    fn jsx_text_whitespace_is_collapsed() {
+        assert_eq!(clean_jsx_text("  a  b  "), "  a  b  ");
+        assert_eq!(clean_jsx_text("a\n   b \n  \n c"), "a b c");
+        assert_eq!(clean_jsx_text("\n    "), "");
+        assert_eq!(clean_jsx_text("&nbsp;\n a"), "\u{A0} a");
+        assert_eq!(clean_jsx_text("a\u{A0}\n\t b"), "a\u{A0} b");
+    }
+
+    #[test]
+    fn character_references_decode() {
+        assert_eq!(decode_entities("&amp;&lt;&gt;&apos;"), "&<>'");
+        assert_eq!(
+            decode_entities("&alpha;&check;&NotEqualTilde;"),
+            "α✓\u{2242}\u{338}"
+        );
+        assert_eq!(
+            decode_entities("&CounterClockwiseContourIntegral;"),
+            "\u{2233}"
+        );
+        assert_eq!(decode_entities("&#65;&#x42;&#X43;"), "ABC");
+        assert_eq!(
+            decode_entities("&#xD800; &#; &#x+41; &#1114112;"),
+            "&#xD800; &#; &#x+41; &#1114112;"
+        );
+        assert_eq!(
+            decode_entities("&amp &unknown; &&amp;"),
+            "&amp &unknown; &&"
+        );
+    }
+}
@@ -4,13 +4,16 @@ use std::{fs::File, io::Read, path::PathBuf};
 
 use clap::Parser;
 use scriptkiddie_lexer::lexer::Lexer;
-use scriptkiddie_parser::parser::Parser as ASTParser;
+use scriptkiddie_parser::parser::{AstParseError, Parser as ASTParser};
 
 /// The config for running scriptkiddie
 #[derive(Parser, Debug)]
 struct Config {
     /// The path of the file to execute
     file: PathBuf,
+    /// Print the file's tokens as JSON instead of running it
+    #[arg(long)]
+    tokens_json: bool,
 }
 
 impl Config {
@@ -26,10 +29,32 @@ impl Config {
     }
 }
 
+/// Lexes and parses a script, or only dumps its tokens as JSON when asked to
+fn run(source: &str, tokens_json: bool) -> Result<(), AstParseError> {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.by_ref().collect::<Result<Vec<_>, _>>()?;
+    let goal = lexer.goal();
+    let mut interner = lexer.into_interner();
+
+    if tokens_json {
+        let dump = serde_json::json!({ "symbols": interner, "tokens": tokens });
+        let json = serde_json::to_string_pretty(&dump).expect("Tokens are always valid JSON");
+        println!("{json}");
+        return Ok(());
+    }
+
+    let mut parser = ASTParser::new(&tokens, &mut interner).with_goal(goal);
+    let _ast = parser.parse_program()?;
+    Ok(())
+}
+
 fn main() {
-    let lines = Config::parse().lines();
-    let lexer = Lexer::new(lines);
-    let tokens: Vec<_> = lexer.collect();
-    let mut parser = ASTParser::new(&tokens);
-    let _ast = parser.parse_program();
+    let config = Config::parse();
+    let tokens_json = config.tokens_json;
+    let lines = config.lines();
+
+    if let Err(err) = run(&lines, tokens_json) {
+        eprintln!("{err}");
+        std::process::exit(1)
+    }
 }